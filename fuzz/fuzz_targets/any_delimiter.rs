@@ -0,0 +1,25 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use cody_c::{AnyDelimiterCodec, DecoderRef};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    /// Might be empty.
+    delimiter: &'a [u8],
+    data: &'a [u8],
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let mut codec = AnyDelimiterCodec::new(input.delimiter);
+    let mut src = input.data;
+
+    while let Ok(Some((_, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &src[size..];
+    }
+
+    let _ = codec.decode_eof_ref(src);
+});
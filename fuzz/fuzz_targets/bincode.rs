@@ -0,0 +1,41 @@
+#![no_main]
+
+use cody_c::{BincodeCodec, DecoderOwned, DecoderRef};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, bincode::Encode, bincode::BorrowDecode)]
+enum Message<'a> {
+    Numbers(u8, u32, i64),
+    Flags(bool, Option<char>),
+    Bytes(&'a [u8]),
+    Str(&'a str),
+    Array([u16; 4]),
+}
+
+#[derive(Debug, bincode::Encode, bincode::Decode)]
+enum OwnedMessage {
+    Numbers(u8, u32, i64),
+    Flags(bool, Option<char>),
+    Array([u16; 4]),
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = BincodeCodec::<Message<'_>>::new();
+    let mut src = data;
+
+    while let Ok(Some((_, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &src[size..];
+    }
+
+    let mut codec = BincodeCodec::<OwnedMessage>::new();
+    let mut src = data.to_vec();
+    let mut src = src.as_mut_slice();
+
+    while let Ok(Some((_, size))) = codec.decode_owned(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &mut src[size..];
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use cody_c::{Checksummed, Crc16Ccitt, DecoderRef, Encoder, LengthCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = Checksummed::new(LengthCodec::new(), Crc16Ccitt);
+
+    // Encoded items decode back to themselves.
+    let mut dst = vec![0_u8; data.len() + 6];
+    let size = codec.encode(data, &mut dst).expect("Must encode");
+
+    assert_eq!(Ok(Some((data, size))), codec.decode_ref(&dst[..size]));
+
+    let mut src = data;
+
+    while let Ok(Some((_, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &src[size..];
+    }
+
+    let _ = codec.decode_eof_ref(src);
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use cody_c::{cobs_max_encoded_len, CobsCodec, Decoder, Encoder};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = CobsCodec::new();
+
+    // Encoded items decode back to themselves.
+    let mut dst = vec![0_u8; cobs_max_encoded_len(data.len()) + 1];
+    let size = codec.encode(data, &mut dst).expect("Must encode");
+
+    assert_eq!(Ok(Some((data, size))), codec.decode(&mut dst[..size]));
+
+    let mut src = data.to_vec();
+    let mut src = &mut src[..];
+
+    loop {
+        let (item, size) = match codec.decode(src) {
+            Ok(Some((item, size))) => (item.len(), size),
+            Ok(None) | Err(_) => break,
+        };
+
+        assert!(item < size && size <= src.len());
+
+        src = &mut src[size..];
+    }
+});
@@ -0,0 +1,111 @@
+#![no_main]
+
+//! Drives a [`FramedRead`] with arbitrary chunk boundaries.
+
+use arbitrary::Arbitrary;
+use cody_c::{
+    AnyDelimiterCodec, AsyncRead, Decoder, FramedRead, LengthCodec, LinesCodec,
+    LinesCodecOwned,
+};
+use core::convert::Infallible;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Codec<'a> {
+    Lines,
+    LinesOwned,
+    Length,
+    AnyDelimiter(&'a [u8]),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    codec: Codec<'a>,
+    /// Every chunk is returned by a single read. Empty chunks are skipped, so EOF is only reached after the last chunk.
+    chunks: Vec<&'a [u8]>,
+}
+
+/// A reader returning its chunks one read at a time.
+struct ChunksReader<'a> {
+    chunks: std::vec::IntoIter<&'a [u8]>,
+    current: &'a [u8],
+}
+
+impl AsyncRead for ChunksReader<'_> {
+    type Error = Infallible;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        while self.current.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let amt = buf.len().min(self.current.len());
+        let (chunk, rest) = self.current.split_at(amt);
+
+        buf[..amt].copy_from_slice(chunk);
+        self.current = rest;
+
+        Ok(amt)
+    }
+}
+
+/// Reads frames until an error occurs, asserting that every frame consumes at least one byte.
+async fn read_frames<D>(decoder: D, input: &Input<'_>)
+where
+    D: for<'a> Decoder<'a>,
+{
+    let total: usize = input.chunks.iter().map(|chunk| chunk.len()).sum();
+
+    let reader = ChunksReader {
+        chunks: input.chunks.clone().into_iter(),
+        current: &[],
+    };
+
+    let mut framed_read = FramedRead::new_with_buffer(decoder, reader, [0_u8; 64]);
+    let mut frames = 0;
+
+    loop {
+        match framed_read.read_frame().await {
+            Ok(Some(_)) => {
+                frames += 1;
+
+                assert!(frames <= total);
+            }
+            Ok(None) => {}
+            Err(_) => break,
+        }
+    }
+}
+
+fuzz_target!(|input: Input<'_>| {
+    futures::executor::block_on(async {
+        match input.codec {
+            Codec::Lines => read_frames(LinesCodec::new(), &input).await,
+            Codec::Length => read_frames(LengthCodec::new(), &input).await,
+            Codec::AnyDelimiter(delimiter) => {
+                read_frames(AnyDelimiterCodec::new(delimiter), &input).await
+            }
+            Codec::LinesOwned => {
+                let total: usize = input.chunks.iter().map(|chunk| chunk.len()).sum();
+
+                let reader = ChunksReader {
+                    chunks: input.chunks.clone().into_iter(),
+                    current: &[],
+                };
+
+                let mut framed_read =
+                    FramedRead::new_with_buffer(LinesCodecOwned::<16>::new(), reader, [0_u8; 64]);
+                let mut frames = 0;
+
+                while framed_read.read_frame_owned().await.is_ok() {
+                    frames += 1;
+
+                    assert!(frames <= total);
+                }
+            }
+        }
+    });
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use cody_c::{Decoder, Encoder, HdlcCodec, HdlcFcs};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (bool, u32, &[u8])| {
+    let (crc32, accm, data) = input;
+    let fcs = if crc32 { HdlcFcs::Crc32 } else { HdlcFcs::Crc16 };
+
+    let mut codec = HdlcCodec::new(fcs).with_accm(accm);
+
+    // Encoded items decode back to themselves.
+    let mut dst = vec![0_u8; 2 * (data.len() + fcs.size()) + 2];
+    let size = codec.encode(data, &mut dst).expect("Must encode");
+
+    assert_eq!(Ok(Some((data, size))), codec.decode(&mut dst[..size]));
+
+    let mut src = data.to_vec();
+    let mut src = &mut src[..];
+
+    // Frames that fail to decode are skipped by the next call.
+    loop {
+        match codec.decode(src) {
+            Ok(Some((_, size))) => {
+                assert!(size > 0 && size <= src.len());
+
+                src = &mut src[size..];
+            }
+            Ok(None) => break,
+            Err(_) => {}
+        }
+    }
+});
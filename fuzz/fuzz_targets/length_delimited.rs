@@ -0,0 +1,52 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use cody_c::{DecoderRef, Endianness, LengthDelimitedCodec, LengthFieldWidth};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    length_field_offset: u8,
+    length_field_width: u8,
+    little_endian: bool,
+    length_adjustment: i16,
+    num_skip: Option<u8>,
+    max_frame_length: u16,
+    data: &'a [u8],
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let length_field_width = match input.length_field_width % 5 {
+        0 => LengthFieldWidth::One,
+        1 => LengthFieldWidth::Two,
+        2 => LengthFieldWidth::Three,
+        3 => LengthFieldWidth::Four,
+        _ => LengthFieldWidth::Eight,
+    };
+
+    let endianness = if input.little_endian {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    };
+
+    let mut builder = LengthDelimitedCodec::builder()
+        .length_field_offset(input.length_field_offset as usize)
+        .length_field_width(length_field_width)
+        .endianness(endianness)
+        .length_adjustment(input.length_adjustment as isize)
+        .max_frame_length(input.max_frame_length as usize);
+
+    if let Some(num_skip) = input.num_skip {
+        builder = builder.num_skip(num_skip as usize);
+    }
+
+    let mut codec = builder.build();
+    let mut src = input.data;
+
+    while let Ok(Some((_, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &src[size..];
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use cody_c::{DecoderRef, LinesCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = LinesCodec::new();
+    let mut src = data;
+
+    while let Ok(Some((_, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &src[size..];
+    }
+
+    let _ = codec.decode_eof_ref(src);
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use cody_c::{demo::codec::PacketCodec, Decoder};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = PacketCodec::new();
+    let mut buf = data.to_vec();
+    let mut start = 0;
+
+    while let Ok(Some((_, size))) = codec.decode(&mut buf[start..]) {
+        assert!(size > 0 && size <= data.len() - start);
+
+        start += size;
+    }
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use cody_c::{Decoder, Encoder, KissHeader, SlipCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (bool, u8, &[u8])| {
+    let (kiss, header, data) = input;
+
+    let mut codec = if kiss {
+        SlipCodec::kiss(KissHeader::from_byte(header))
+    } else {
+        SlipCodec::new()
+    };
+
+    // Encoded items decode back to themselves, plain SLIP drops empty frames.
+    let mut dst = vec![0_u8; 2 * data.len() + 4];
+    let size = codec.encode(data, &mut dst).expect("Must encode");
+
+    if kiss || !data.is_empty() {
+        assert_eq!(Ok(Some((data, size))), codec.decode(&mut dst[..size]));
+    }
+
+    codec.clear();
+
+    let mut src = data.to_vec();
+    let mut src = &mut src[..];
+
+    while let Ok(Some((_, size))) = codec.decode(src) {
+        assert!(size > 0 && size <= src.len());
+
+        src = &mut src[size..];
+    }
+});
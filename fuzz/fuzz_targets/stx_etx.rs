@@ -0,0 +1,37 @@
+#![no_main]
+
+use cody_c::{BlockCheck, Decoder, Encoder, StxEtxCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (u8, &[u8])| {
+    let (block_check, data) = input;
+    let block_check = match block_check % 3 {
+        0 => BlockCheck::None,
+        1 => BlockCheck::Xor,
+        _ => BlockCheck::Lrc,
+    };
+
+    let mut codec = StxEtxCodec::new().with_block_check(block_check);
+
+    // Encoded items decode back to themselves.
+    let mut dst = vec![0_u8; 2 * data.len() + 3];
+    let size = codec.encode(data, &mut dst).expect("Must encode");
+
+    assert_eq!(Ok(Some((data, size))), codec.decode(&mut dst[..size]));
+
+    let mut src = data.to_vec();
+    let mut src = &mut src[..];
+
+    // Frames that fail to decode are skipped by the next call.
+    loop {
+        match codec.decode(src) {
+            Ok(Some((_, size))) => {
+                assert!(size > 0 && size <= src.len());
+
+                src = &mut src[size..];
+            }
+            Ok(None) => break,
+            Err(_) => {}
+        }
+    }
+});
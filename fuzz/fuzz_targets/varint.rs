@@ -0,0 +1,22 @@
+#![no_main]
+
+use cody_c::{DecoderRef, Encoder, VarintLengthCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = VarintLengthCodec::new().with_max_frame_length(4096);
+    let mut src = data;
+    let mut dst = [0_u8; 4096 + 2];
+
+    while let Ok(Some((item, size))) = codec.decode_ref(src) {
+        assert!(size > 0 && size <= src.len());
+
+        // Decoded frames use minimal prefixes and must encode back to the same bytes.
+        let encoded = codec.clone().encode(item, &mut dst).expect("Must encode");
+        assert_eq!(&src[..size], &dst[..encoded]);
+
+        src = &src[size..];
+    }
+
+    let _ = codec.decode_eof_ref(src);
+});
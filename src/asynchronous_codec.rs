@@ -0,0 +1,518 @@
+//! Implementations of [`Asynchronous-codec's Decoder`](::asynchronous_codec::Decoder) and [`Asynchronous-codec's Encoder`](::asynchronous_codec::Encoder) for the crate's codecs.
+//!
+//! - Borrowing codecs like [`LinesCodec`] decode into [`Bytes`] pointing into the read buffer and encode `&[u8]`.
+//! - Owned codecs like [`LinesCodecOwned`] decode into and encode [`heapless::Vec`].
+//! - [`BincodeCodec`] decodes into and encodes its bincode data structure.
+
+use core::convert::Infallible;
+
+use ::asynchronous_codec::{Bytes, BytesMut};
+use futures::io::{Error, ErrorKind};
+use heapless::Vec;
+
+use crate::{
+    codec::{
+        AnyDelimiterCodec, AnyDelimiterCodecOwned, AnyDelimiterEncodeError, BytesCodec,
+        BytesCodecOwned, BytesEncodeError, LengthCodec, LengthCodecOwned, LengthEncodeError,
+        LinesCodec, LinesCodecOwned, LinesEncodeError,
+    },
+    decode::{DecoderOwned, DecoderRef},
+    encode::{BufferTooSmallError, Encoder},
+};
+
+#[cfg(feature = "bincode")]
+use crate::codec::{BincodeCodec, BincodeDecodeError, BincodeEncodeError};
+
+/// An error that can occur when decoding a frame using [`Asynchronous-codec's Decoder`](::asynchronous_codec::Decoder).
+#[derive(Debug)]
+pub enum AsynchronousDecodeError<E> {
+    /// An IO error occurred.
+    IO(Error),
+    /// The crate's decoder returned an error.
+    Decode(E),
+}
+
+impl<E> From<Error> for AsynchronousDecodeError<E> {
+    fn from(err: Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl<E> core::fmt::Display for AsynchronousDecodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {}", err),
+            Self::Decode(err) => write!(f, "Decode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for AsynchronousDecodeError<E> where
+    E: core::fmt::Display + core::fmt::Debug
+{
+}
+
+/// An error that can occur when encoding a frame using [`Asynchronous-codec's Encoder`](::asynchronous_codec::Encoder).
+#[derive(Debug)]
+pub enum AsynchronousEncodeError<E> {
+    /// An IO error occurred.
+    IO(Error),
+    /// The crate's encoder returned an error.
+    Encode(E),
+}
+
+impl<E> From<Error> for AsynchronousEncodeError<E> {
+    fn from(err: Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl<E> core::fmt::Display for AsynchronousEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {}", err),
+            Self::Encode(err) => write!(f, "Encode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for AsynchronousEncodeError<E> where
+    E: core::fmt::Display + core::fmt::Debug
+{
+}
+
+/// Returns an error if bytes are left in `src` at the end of the stream.
+fn bytes_remaining<T, E>(
+    src: &BytesMut,
+    eof: bool,
+) -> Result<Option<T>, AsynchronousDecodeError<E>> {
+    if eof && !src.is_empty() {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "bytes remaining on stream").into());
+    }
+
+    Ok(None)
+}
+
+/// Decodes a frame borrowed from `src` and splits it off as [`Bytes`] without copying.
+fn decode_bytes<C>(
+    codec: &mut C,
+    src: &mut BytesMut,
+    eof: bool,
+) -> Result<Option<Bytes>, AsynchronousDecodeError<Infallible>>
+where
+    C: for<'buf> DecoderRef<'buf, Item = &'buf [u8], Error = Infallible>,
+{
+    let result = if eof {
+        codec.decode_eof_ref(src)
+    } else {
+        codec.decode_ref(src)
+    };
+
+    match result {
+        Ok(Some((frame, size))) => {
+            // The frame is a subslice of the consumed bytes, e.g. without the length prefix or the delimiter.
+            let start = (frame.as_ptr() as usize)
+                .checked_sub(src.as_ptr() as usize)
+                .filter(|start| start + frame.len() <= size)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "frame outside of the consumed bytes",
+                    )
+                })?;
+            let end = start + frame.len();
+
+            Ok(Some(src.split_to(size).freeze().slice(start..end)))
+        }
+        Ok(None) => bytes_remaining(src, eof),
+        Err(err) => match err {},
+    }
+}
+
+/// Decodes an owned frame from `src` and advances it by the consumed size.
+fn decode_owned<C>(
+    codec: &mut C,
+    src: &mut BytesMut,
+    eof: bool,
+) -> Result<Option<C::Item>, AsynchronousDecodeError<C::Error>>
+where
+    C: DecoderOwned,
+{
+    let result = if eof {
+        codec.decode_eof_owned(src)
+    } else {
+        codec.decode_owned(src)
+    };
+
+    match result {
+        Ok(Some((item, size))) => {
+            let _ = src.split_to(size);
+
+            Ok(Some(item))
+        }
+        Ok(None) => bytes_remaining(src, eof),
+        Err(err) => Err(AsynchronousDecodeError::Decode(err)),
+    }
+}
+
+/// The minimum number of bytes reserved at the end of `dst` to encode a frame.
+const MIN_ENCODE_CAPACITY: usize = 64;
+
+/// The maximum number of bytes reserved at the end of `dst` to encode a frame.
+pub const MAX_ENCODE_CAPACITY: usize = 8 * 1024 * 1024;
+
+/// Encodes a frame into the spare capacity at the end of `dst`.
+///
+/// The encoded size is measured rather than predicted: while the encoder reports that the scratch area is too small (see [`BufferTooSmallError`]),
+/// it is doubled and the item is encoded again, up to [`MAX_ENCODE_CAPACITY`] bytes.
+fn encode<C, I>(
+    codec: &mut C,
+    item: I,
+    dst: &mut BytesMut,
+) -> Result<(), AsynchronousEncodeError<C::Error>>
+where
+    C: Encoder<I>,
+    C::Error: BufferTooSmallError,
+    I: Clone,
+{
+    let start = dst.len();
+    let mut size = (dst.capacity() - start).clamp(MIN_ENCODE_CAPACITY, MAX_ENCODE_CAPACITY);
+
+    loop {
+        dst.resize(start + size, 0);
+
+        match codec.encode(item.clone(), &mut dst[start..]) {
+            Ok(size) => {
+                dst.truncate(start + size);
+
+                return Ok(());
+            }
+            Err(err) if err.is_buffer_too_small() && size < MAX_ENCODE_CAPACITY => {
+                size = core::cmp::min(size * 2, MAX_ENCODE_CAPACITY);
+            }
+            Err(err) => {
+                dst.truncate(start);
+
+                return Err(AsynchronousEncodeError::Encode(err));
+            }
+        }
+    }
+}
+
+/// Implements the asynchronous-codec traits for a codec decoding borrowed slices and encoding `&[u8]`.
+macro_rules! impl_bytes_codec {
+    ([$($generics:tt)*] $codec:ty, $error:ty) => {
+        impl<$($generics)*> ::asynchronous_codec::Decoder for $codec {
+            type Item = Bytes;
+            type Error = AsynchronousDecodeError<Infallible>;
+
+            fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+                decode_bytes(self, src, false)
+            }
+
+            fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+                decode_bytes(self, src, true)
+            }
+        }
+
+        impl<$($generics)*> ::asynchronous_codec::Encoder for $codec {
+            type Item<'a> = &'a [u8];
+            type Error = AsynchronousEncodeError<$error>;
+
+            fn encode(&mut self, item: Self::Item<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+                encode(self, item, dst)
+            }
+        }
+    };
+}
+
+/// Implements the asynchronous-codec traits for a codec decoding and encoding [`heapless::Vec`].
+macro_rules! impl_owned_codec {
+    ([$($generics:tt)*] $codec:ty, $error:ty) => {
+        impl<$($generics)*> ::asynchronous_codec::Decoder for $codec {
+            type Item = Vec<u8, N>;
+            type Error = AsynchronousDecodeError<()>;
+
+            fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+                decode_owned(self, src, false)
+            }
+
+            fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+                decode_owned(self, src, true)
+            }
+        }
+
+        impl<$($generics)*> ::asynchronous_codec::Encoder for $codec {
+            type Item<'a> = Vec<u8, N>;
+            type Error = AsynchronousEncodeError<$error>;
+
+            fn encode(&mut self, item: Self::Item<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+                encode(self, item, dst)
+            }
+        }
+    };
+}
+
+impl_bytes_codec!([] BytesCodec, BytesEncodeError);
+impl_bytes_codec!([] LinesCodec, LinesEncodeError);
+impl_bytes_codec!([] LengthCodec, LengthEncodeError);
+impl_bytes_codec!(['d] AnyDelimiterCodec<'d>, AnyDelimiterEncodeError);
+
+impl_owned_codec!([const N: usize] BytesCodecOwned<N>, BytesEncodeError);
+impl_owned_codec!([const N: usize] LinesCodecOwned<N>, LinesEncodeError);
+impl_owned_codec!([const N: usize] LengthCodecOwned<N>, LengthEncodeError);
+impl_owned_codec!(['d, const N: usize] AnyDelimiterCodecOwned<'d, N>, AnyDelimiterEncodeError);
+
+#[cfg(feature = "bincode")]
+impl<D, C> ::asynchronous_codec::Decoder for BincodeCodec<D, C>
+where
+    D: bincode::Decode,
+    C: bincode::config::Config,
+{
+    type Item = D;
+    type Error = AsynchronousDecodeError<BincodeDecodeError>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        decode_owned(self, src, false)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        decode_owned(self, src, true)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<D, C> ::asynchronous_codec::Encoder for BincodeCodec<D, C>
+where
+    D: bincode::Encode,
+    C: bincode::config::Config,
+{
+    type Item<'a> = D;
+    type Error = AsynchronousEncodeError<BincodeEncodeError>;
+
+    fn encode(&mut self, item: Self::Item<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        encode(self, &item, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use ::asynchronous_codec::{FramedRead, FramedWrite};
+    use futures::{SinkExt, StreamExt};
+
+    use crate::test::init_tracing;
+
+    use super::*;
+
+    fn items() -> StdVec<Vec<u8, 32>> {
+        std::vec![
+            Vec::from_slice(b"Hello").unwrap(),
+            Vec::from_slice(b"Hello, world!").unwrap(),
+            Vec::from_slice(b"Hei").unwrap(),
+            Vec::from_slice(b"sup").unwrap(),
+            Vec::from_slice(b"Hey").unwrap(),
+        ]
+    }
+
+    /// Sends `$items` through an `asynchronous_codec::FramedWrite` into an in-memory pipe and collects them from an `asynchronous_codec::FramedRead` on the other end.
+    macro_rules! roundtrip {
+        ($codec:expr, $items:expr) => {{
+            let (read, write) = sluice::pipe::pipe();
+            let items = $items;
+
+            let send = async move {
+                let mut sink = FramedWrite::new(write, $codec);
+
+                for item in items {
+                    sink.send(item).await.expect("Must send");
+                }
+
+                sink.close().await.expect("Must close");
+            };
+
+            let collect = FramedRead::new(read, $codec).collect::<StdVec<_>>();
+
+            let ((), collected) = tokio::join!(send, collect);
+
+            collected
+                .into_iter()
+                .collect::<Result<StdVec<_>, _>>()
+                .expect("Must decode")
+        }};
+    }
+
+    /// Roundtrips a borrowing codec and compares the decoded [`Bytes`] with the sent slices.
+    macro_rules! roundtrip_bytes {
+        ($codec:expr) => {{
+            let items = items();
+
+            let collected = roundtrip!($codec, items.iter().map(|item| item.as_slice()));
+
+            assert_eq!(
+                items,
+                collected
+                    .iter()
+                    .map(|item| Vec::<u8, 32>::from_slice(item).unwrap())
+                    .collect::<StdVec<_>>()
+            );
+        }};
+    }
+
+    #[tokio::test]
+    async fn lines() {
+        init_tracing();
+
+        roundtrip_bytes!(LinesCodec::new());
+
+        assert_eq!(items(), roundtrip!(LinesCodecOwned::<32>::new(), items()));
+    }
+
+    #[tokio::test]
+    async fn length() {
+        init_tracing();
+
+        roundtrip_bytes!(LengthCodec::new());
+
+        assert_eq!(items(), roundtrip!(LengthCodecOwned::<32>::new(), items()));
+    }
+
+    #[tokio::test]
+    async fn any_delimiter() {
+        init_tracing();
+
+        roundtrip_bytes!(AnyDelimiterCodec::new(b"###"));
+
+        assert_eq!(
+            items(),
+            roundtrip!(AnyDelimiterCodecOwned::<32>::new(b"###"), items())
+        );
+    }
+
+    #[test]
+    fn encode_grows_scratch_area() {
+        use ::asynchronous_codec::Decoder as _;
+
+        init_tracing();
+
+        let item = Vec::<u8, 512>::from_slice(&[b'a'; 500]).unwrap();
+
+        let mut codec = LinesCodecOwned::<512>::new();
+        let mut dst = BytesMut::new();
+
+        ::asynchronous_codec::Encoder::encode(&mut codec, item.clone(), &mut dst)
+            .expect("Must encode");
+        ::asynchronous_codec::Encoder::encode(&mut codec, item.clone(), &mut dst)
+            .expect("Must encode");
+
+        assert_eq!(2 * 502, dst.len());
+
+        for _ in 0..2 {
+            assert_eq!(
+                Some(&item),
+                codec.decode(&mut dst).expect("Must decode").as_ref()
+            );
+        }
+
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn encode_stops_growing_at_max_capacity() {
+        init_tracing();
+
+        let item = std::vec![b'a'; MAX_ENCODE_CAPACITY + 1];
+
+        let mut codec = BytesCodec::new();
+        let mut dst = BytesMut::from(&b"Hello"[..]);
+
+        assert!(matches!(
+            ::asynchronous_codec::Encoder::encode(&mut codec, &item, &mut dst),
+            Err(AsynchronousEncodeError::Encode(
+                BytesEncodeError::BufferTooSmall
+            ))
+        ));
+
+        assert_eq!(b"Hello", &dst[..]);
+
+        let item = &item[..MAX_ENCODE_CAPACITY];
+
+        ::asynchronous_codec::Encoder::encode(&mut codec, item, &mut dst).expect("Must encode");
+
+        assert_eq!(5 + MAX_ENCODE_CAPACITY, dst.len());
+    }
+
+    #[tokio::test]
+    async fn bytes_remaining_on_stream() {
+        init_tracing();
+
+        let (read, mut write) = sluice::pipe::pipe();
+
+        let send = async move {
+            use futures::AsyncWriteExt;
+
+            write.write_all(b"Hello\r\nwor").await.expect("Must write");
+        };
+
+        // The stream keeps yielding the error, since the remaining bytes are never consumed.
+        let collect = FramedRead::new(read, LinesCodec::new())
+            .take(2)
+            .collect::<StdVec<_>>();
+
+        let ((), collected) = tokio::join!(send, collect);
+
+        assert_eq!(2, collected.len());
+        assert_eq!(
+            b"Hello",
+            collected[0].as_ref().expect("Must decode").as_ref()
+        );
+        assert!(
+            matches!(&collected[1], Err(AsynchronousDecodeError::IO(err)) if err.kind() == ErrorKind::UnexpectedEof)
+        );
+    }
+
+    #[cfg(feature = "bincode")]
+    #[tokio::test]
+    async fn bincode() {
+        use crate::codec::BincodeCodec;
+
+        #[derive(Debug, Clone, PartialEq, bincode::Encode, bincode::Decode)]
+        struct Message {
+            id: u32,
+            name: std::string::String,
+        }
+
+        init_tracing();
+
+        let messages = std::vec![
+            Message {
+                id: 1,
+                name: "Hello".into(),
+            },
+            Message {
+                id: 2,
+                name: "Hello, world!".into(),
+            },
+            Message {
+                id: 3,
+                name: "Hello, world! ".repeat(100),
+            },
+        ];
+
+        assert_eq!(
+            messages,
+            roundtrip!(BincodeCodec::<Message>::new(), messages.clone())
+        );
+    }
+}
@@ -5,8 +5,8 @@ use core::convert::Infallible;
 use heapless::Vec;
 
 use crate::{
-    decode::{Decoder, DecoderOwned},
-    encode::Encoder,
+    decode::{Decoder, DecoderOwned, DecoderRef},
+    encode::{BufferTooSmallError, Encoder, EncoderEof},
 };
 
 /// A codec that decodes a sequence of bytes ending with a `delimiter` into a sequence of bytes and encodes a sequence of bytes into a sequence of bytes ending with a `delimiter`.
@@ -50,7 +50,16 @@ impl<'buf> Decoder<'buf> for AnyDelimiterCodec<'_> {
     type Error = Infallible;
 
     fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
-        if src.len() < self.delimiter.len() {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for AnyDelimiterCodec<'_> {
+    type Item = &'buf [u8];
+    type Error = Infallible;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        if src.is_empty() || src.len() < self.delimiter.len() {
             return Ok(None);
         }
 
@@ -63,7 +72,8 @@ impl<'buf> Decoder<'buf> for AnyDelimiterCodec<'_> {
             }
             Some(last_byte) => {
                 while self.seen < src.len() {
-                    if src[self.seen] == *last_byte {
+                    // The delimiter can not end before its length.
+                    if self.seen + 1 >= self.delimiter.len() && src[self.seen] == *last_byte {
                         let src_delimiter =
                             &src[self.seen + 1 - self.delimiter.len()..self.seen + 1];
 
@@ -105,6 +115,12 @@ impl core::fmt::Display for AnyDelimiterEncodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for AnyDelimiterEncodeError {}
 
+impl BufferTooSmallError for AnyDelimiterEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
 impl Encoder<&[u8]> for AnyDelimiterCodec<'_> {
     type Error = AnyDelimiterEncodeError;
 
@@ -122,6 +138,10 @@ impl Encoder<&[u8]> for AnyDelimiterCodec<'_> {
     }
 }
 
+impl EncoderEof for AnyDelimiterCodec<'_> {
+    type Error = AnyDelimiterEncodeError;
+}
+
 /// An owned [`AnyDelimiterCodec`].
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -179,6 +199,22 @@ impl<const N: usize> DecoderOwned for AnyDelimiterCodecOwned<'_, N> {
     }
 }
 
+impl<'buf, const N: usize> DecoderRef<'buf> for AnyDelimiterCodecOwned<'_, N> {
+    type Item = Vec<u8, N>;
+    type Error = ();
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src) {
+            Ok(Some((bytes, size))) => {
+                let item = Vec::from_slice(bytes)?;
+                Ok(Some((item, size)))
+            }
+            Ok(None) => Ok(None),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 impl<const N: usize> Encoder<Vec<u8, N>> for AnyDelimiterCodecOwned<'_, N> {
     type Error = AnyDelimiterEncodeError;
 
@@ -187,18 +223,20 @@ impl<const N: usize> Encoder<Vec<u8, N>> for AnyDelimiterCodecOwned<'_, N> {
     }
 }
 
+impl<const N: usize> EncoderEof for AnyDelimiterCodecOwned<'_, N> {
+    type Error = AnyDelimiterEncodeError;
+}
+
 #[cfg(test)]
 mod test {
     extern crate std;
 
-    use std::vec::Vec;
-
-    use futures::{pin_mut, SinkExt, StreamExt};
-    use tokio::io::AsyncWriteExt;
-
     use crate::{
-        error, framed_read, sink_stream, test::init_tracing, tokio::Compat, FramedRead,
-        FramedReadError, FramedWrite,
+        test::init_tracing,
+        testing::{
+            assert_codec_conformance_items, assert_decode_buffer_sizes,
+            assert_decoder_conformance_truncated,
+        },
     };
 
     use super::*;
@@ -207,72 +245,74 @@ mod test {
     async fn framed_read() {
         init_tracing();
 
-        let items: &[&[u8]] = &[
-            b"jh asjd##ppppppppppppppp##",
-            b"k hb##jsjuwjal kadj##jsadhjiu##w",
-            b"##jal kadjjsadhjiuwqens ##",
-            b"nd ",
-            b"yxxcjajsdi##askdn as",
-            b"jdasd##iouqw es",
-            b"sd##k",
-        ];
-
-        let decoder = AnyDelimiterCodec::new(b"##");
-
-        let expected: &[&[u8]] = &[];
-        framed_read!(items, expected, decoder, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 4, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 4, BufferTooSmall);
-
-        let expected: &[&[u8]] = &[b"jh asjd"];
-        framed_read!(items, expected, decoder, 16, BufferTooSmall);
-
-        let expected: &[&[u8]] = &[
-            b"jh asjd",
-            b"ppppppppppppppp",
-            b"k hb",
-            b"jsjuwjal kadj",
-            b"jsadhjiu",
-            b"w",
-            b"jal kadjjsadhjiuwqens ",
-            b"nd yxxcjajsdi",
-            b"askdn asjdasd",
-            b"iouqw essd",
-        ];
-        framed_read!(items, expected, decoder, 32, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 1, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 2, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 4, BytesRemainingOnStream);
-
-        framed_read!(items, expected, decoder);
+        let input = b"jh asjd##ppppppppppppppp##k hb##jsjuwjal kadj##jsadhjiu##w##jal kadjjsadhjiuwqens ##nd yxxcjajsdi##askdn asjdasd##iouqw essd##k";
+
+        assert_decode_buffer_sizes!(
+            AnyDelimiterCodec::new(b"##"),
+            input,
+            &[],
+            BufferTooSmall,
+            [1, 2, 4]
+        );
+
+        assert_decode_buffer_sizes!(
+            AnyDelimiterCodec::new(b"##"),
+            input,
+            &[b"jh asjd"],
+            BufferTooSmall,
+            [16]
+        );
+
+        assert_decode_buffer_sizes!(
+            AnyDelimiterCodec::new(b"##"),
+            input,
+            &[
+                b"jh asjd",
+                b"ppppppppppppppp",
+                b"k hb",
+                b"jsjuwjal kadj",
+                b"jsadhjiu",
+                b"w",
+                b"jal kadjjsadhjiuwqens ",
+                b"nd yxxcjajsdi",
+                b"askdn asjdasd",
+                b"iouqw essd",
+            ],
+            BytesRemainingOnStream,
+            [32, 1024]
+        );
     }
 
-    #[tokio::test]
-    async fn sink_stream() {
+    #[test]
+    fn hostile_input() {
+        let mut codec = AnyDelimiterCodec::new(b"");
+
+        assert_eq!(None, codec.decode_ref(b"").unwrap());
+        assert_eq!(Some((&b"a"[..], 1)), codec.decode_ref(b"ab").unwrap());
+
+        // The last byte of the delimiter comes before a full delimiter could fit.
+        let mut codec = AnyDelimiterCodec::new(b"##");
+
+        assert_eq!(None, codec.decode_ref(b"#abc").unwrap());
+        assert_eq!(
+            Some((&b"#abc"[..], 6)),
+            codec.decode_ref(b"#abc##").unwrap()
+        );
+    }
+
+    #[test]
+    fn conformance() {
         init_tracing();
 
-        let items: Vec<heapless::Vec<u8, 32>> = std::vec![
-            heapless::Vec::from_slice(b"Hello").unwrap(),
-            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
-            heapless::Vec::from_slice(b"Hei").unwrap(),
-            heapless::Vec::from_slice(b"sup").unwrap(),
-            heapless::Vec::from_slice(b"Hey").unwrap(),
-        ];
+        assert_codec_conformance_items::<32, _>(AnyDelimiterCodecOwned::<32>::new(b"###"), &[]);
 
-        let decoder = AnyDelimiterCodecOwned::<32>::new(b"###");
-        let encoder = AnyDelimiterCodecOwned::<32>::new(b"###");
+        let input = b"Hello###Hello, world!###Hei##sup###Hey###How are y";
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei##sup", b"Hey"];
 
-        sink_stream!(encoder, decoder, items);
+        assert_decoder_conformance_truncated(
+            AnyDelimiterCodecOwned::<32>::new(b"###"),
+            input,
+            expected,
+        );
     }
 }
@@ -3,36 +3,130 @@
 use core::marker::PhantomData;
 
 use bincode::{
+    config::{Config, Configuration},
     error::{DecodeError, EncodeError},
     BorrowDecode, Decode, Encode,
 };
 
-use crate::{Decoder, DecoderOwned, Encoder, SIZE_OF_LENGTH};
-
-use super::LengthCodec;
+use crate::{
+    BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof,
+    LengthDelimitedCodec, LengthDelimitedDecodeError, LengthDelimitedEncodeError,
+};
 
 /// A codec that decodes a sequence of bytes with a payload length prefix into a bincode data structure and encodes a bincode data structure into a sequence of bytes with a payload length prefix.
-#[derive(Debug, Clone, Default)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct BincodeCodec<D> {
-    length_codec: LengthCodec,
+///
+/// The bincode configuration `C` defaults to [`bincode::config::standard`] and the length prefix defaults to a big-endian `u32`.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{BincodeCodec, LengthDelimitedCodec, LengthFieldWidth};
+///
+/// // Fixed-int big-endian peers, with a decode size limit and a two bytes length prefix.
+/// let config = bincode::config::standard()
+///     .with_big_endian()
+///     .with_fixed_int_encoding()
+///     .with_limit::<1024>();
+///
+/// let codec = BincodeCodec::<u32, _>::new_with_config(config).with_length_codec(
+///     LengthDelimitedCodec::builder()
+///         .length_field_width(LengthFieldWidth::Two)
+///         .max_frame_length(1024)
+///         .build(),
+/// );
+/// ```
+pub struct BincodeCodec<D, C = Configuration> {
+    config: C,
+    length_codec: LengthDelimitedCodec,
     _de: PhantomData<D>,
 }
 
 impl<D> BincodeCodec<D> {
-    /// Creates a new [`BincodeCodec`].
+    /// Creates a new [`BincodeCodec`] with the standard bincode configuration.
     #[inline]
     pub const fn new() -> Self {
+        Self::new_with_config(bincode::config::standard())
+    }
+}
+
+impl<D> Default for BincodeCodec<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D, C> BincodeCodec<D, C> {
+    /// Creates a new [`BincodeCodec`] with the given bincode `config`.
+    #[inline]
+    pub const fn new_with_config(config: C) -> Self {
+        Self {
+            config,
+            length_codec: LengthDelimitedCodec::new(),
+            _de: PhantomData,
+        }
+    }
+
+    /// Sets the codec of the payload length prefix.
+    ///
+    /// The frame is the length field followed by the payload, so the `length_codec` must have a `length_field_offset` of `0` and the default `num_skip`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `length_codec` has a head before the length field or a `num_skip` other than the default.
+    #[inline]
+    pub const fn with_length_codec(mut self, length_codec: LengthDelimitedCodec) -> Self {
+        assert!(
+            length_codec.is_encodable(),
+            "The length codec must have a length field offset of 0 and the default num_skip"
+        );
+
+        self.length_codec = length_codec;
+        self
+    }
+
+    /// Returns the bincode configuration.
+    #[inline]
+    pub const fn config(&self) -> &C {
+        &self.config
+    }
+
+    /// Returns the codec of the payload length prefix.
+    #[inline]
+    pub const fn length_codec(&self) -> &LengthDelimitedCodec {
+        &self.length_codec
+    }
+}
+
+impl<D, C: Clone> Clone for BincodeCodec<D, C> {
+    fn clone(&self) -> Self {
         Self {
-            length_codec: LengthCodec::new(),
+            config: self.config.clone(),
+            length_codec: self.length_codec.clone(),
             _de: PhantomData,
         }
     }
 }
 
+impl<D, C> core::fmt::Debug for BincodeCodec<D, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BincodeCodec")
+            .field("length_codec", &self.length_codec)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<D, C> defmt::Format for BincodeCodec<D, C> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "BincodeCodec {{ length_codec: {} }}", self.length_codec)
+    }
+}
+
 /// An error that can occur when decoding a sequence of bytes with a payload length prefix into a bincode data structure.
 #[derive(Debug)]
 pub enum BincodeDecodeError {
+    /// The payload length prefix is invalid.
+    Length(LengthDelimitedDecodeError),
     /// A Bincode error occurred.
     Decode(DecodeError),
 }
@@ -41,6 +135,7 @@ pub enum BincodeDecodeError {
 impl defmt::Format for BincodeDecodeError {
     fn format(&self, f: defmt::Formatter) {
         match self {
+            Self::Length(err) => defmt::write!(f, "Length error: {}", err),
             Self::Decode(_) => defmt::write!(f, "Decode error"),
         }
     }
@@ -49,6 +144,7 @@ impl defmt::Format for BincodeDecodeError {
 impl core::fmt::Display for BincodeDecodeError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
+            Self::Length(err) => write!(f, "Length error: {}", err),
             Self::Decode(err) => write!(f, "Decode error: {}", err),
         }
     }
@@ -57,22 +153,36 @@ impl core::fmt::Display for BincodeDecodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for BincodeDecodeError {}
 
-impl<'buf, D> Decoder<'buf> for BincodeCodec<D>
+impl<'buf, D, C> Decoder<'buf> for BincodeCodec<D, C>
 where
     D: BorrowDecode<'buf>,
+    C: Config,
 {
     type Item = D;
     type Error = BincodeDecodeError;
 
     fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf, D, C> DecoderRef<'buf> for BincodeCodec<D, C>
+where
+    D: BorrowDecode<'buf>,
+    C: Config,
+{
+    type Item = D;
+    type Error = BincodeDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
         match self
             .length_codec
-            .decode(src)
-            .expect("<LengthCodec as Decoder>::Error must be infallible")
+            .decode_ref(src)
+            .map_err(BincodeDecodeError::Length)?
         {
             None => Ok(None),
             Some((bytes, size)) => {
-                let (de, _) = bincode::borrow_decode_from_slice(bytes, bincode::config::standard())
+                let (de, _) = bincode::borrow_decode_from_slice(bytes, self.config)
                     .map_err(BincodeDecodeError::Decode)?;
 
                 let item = (de, size);
@@ -90,8 +200,8 @@ pub enum BincodeEncodeError {
     BufferTooSmall,
     /// A Bincode error occurred.
     Encode(EncodeError),
-    /// The payload size is greater than u32::MAX.
-    PayloadTooLarge,
+    /// The payload does not fit into the payload length prefix.
+    Length(LengthDelimitedEncodeError),
 }
 
 #[cfg(feature = "defmt")]
@@ -100,7 +210,7 @@ impl defmt::Format for BincodeEncodeError {
         match self {
             Self::BufferTooSmall => defmt::write!(f, "Buffer too small"),
             Self::Encode(_) => defmt::write!(f, "Encode error"),
-            Self::PayloadTooLarge => defmt::write!(f, "Payload too large"),
+            Self::Length(err) => defmt::write!(f, "Length error: {}", err),
         }
     }
 }
@@ -110,7 +220,7 @@ impl core::fmt::Display for BincodeEncodeError {
         match self {
             Self::BufferTooSmall => write!(f, "Buffer too small"),
             Self::Encode(err) => write!(f, "Encode error: {}", err),
-            Self::PayloadTooLarge => write!(f, "Payload too large"),
+            Self::Length(err) => write!(f, "Length error: {}", err),
         }
     }
 }
@@ -118,39 +228,64 @@ impl core::fmt::Display for BincodeEncodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for BincodeEncodeError {}
 
-impl<D> Encoder<D> for BincodeCodec<D>
+impl BufferTooSmallError for BincodeEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        match self {
+            Self::BufferTooSmall | Self::Encode(EncodeError::UnexpectedEnd) => true,
+            Self::Length(err) => err.is_buffer_too_small(),
+            Self::Encode(_) => false,
+        }
+    }
+}
+
+impl<D, C> Encoder<D> for BincodeCodec<D, C>
 where
     D: Encode,
+    C: Config,
 {
     type Error = BincodeEncodeError;
 
     fn encode(&mut self, item: D, dst: &mut [u8]) -> Result<usize, Self::Error> {
-        if dst.len() < SIZE_OF_LENGTH {
-            return Err(BincodeEncodeError::BufferTooSmall);
-        }
+        <Self as Encoder<&D>>::encode(self, &item, dst)
+    }
+}
 
-        let payload_len = bincode::encode_into_slice(
-            item,
-            &mut dst[SIZE_OF_LENGTH..],
-            bincode::config::standard(),
-        )
-        .map_err(BincodeEncodeError::Encode)?;
+/// Encodes a bincode data structure by reference, e.g. to send items that are not [`Clone`] through a `TokioCodec`.
+impl<D, C> Encoder<&D> for BincodeCodec<D, C>
+where
+    D: Encode,
+    C: Config,
+{
+    type Error = BincodeEncodeError;
 
-        if payload_len > u32::MAX as usize {
-            return Err(BincodeEncodeError::PayloadTooLarge);
+    fn encode(&mut self, item: &D, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let prefix_len = self.length_codec.length_field_width().size();
+
+        if dst.len() < prefix_len {
+            return Err(BincodeEncodeError::BufferTooSmall);
         }
 
-        dst[0..SIZE_OF_LENGTH].copy_from_slice(&(payload_len as u32).to_be_bytes());
+        let payload_len = bincode::encode_into_slice(item, &mut dst[prefix_len..], self.config)
+            .map_err(BincodeEncodeError::Encode)?;
 
-        let packet_len = payload_len + SIZE_OF_LENGTH;
+        self.length_codec
+            .encode_length(payload_len, dst)
+            .map_err(BincodeEncodeError::Length)?;
+
+        let packet_len = payload_len + prefix_len;
 
         Ok(packet_len)
     }
 }
 
-impl<D> DecoderOwned for BincodeCodec<D>
+impl<D, C> EncoderEof for BincodeCodec<D, C> {
+    type Error = BincodeEncodeError;
+}
+
+impl<D, C> DecoderOwned for BincodeCodec<D, C>
 where
     D: Decode,
+    C: Config,
 {
     type Item = D;
     type Error = BincodeDecodeError;
@@ -159,11 +294,11 @@ where
         match self
             .length_codec
             .decode(src)
-            .expect("<LengthCodec as Decoder>::Error must be infallible")
+            .map_err(BincodeDecodeError::Length)?
         {
             None => Ok(None),
             Some((bytes, size)) => {
-                let (de, _) = bincode::decode_from_slice(bytes, bincode::config::standard())
+                let (de, _) = bincode::decode_from_slice(bytes, self.config)
                     .map_err(BincodeDecodeError::Decode)?;
 
                 let item = (de, size);
@@ -174,143 +309,6 @@ where
     }
 }
 
-#[cfg(all(feature = "std", feature = "tokio"))]
-#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "tokio"))))]
-pub mod tokio_codec {
-    //! Tokio codec implementation for [`BincodeCodec`].
-
-    use bincode::{
-        error::{DecodeError, EncodeError},
-        Decode, Encode,
-    };
-    use tokio_util::{
-        bytes::{Buf, BufMut, BytesMut},
-        codec::{Decoder, Encoder},
-    };
-
-    use crate::SIZE_OF_LENGTH;
-
-    use super::BincodeCodec;
-
-    /// An error that can occur when decoding a sequence of bytes with a payload length prefix into a bincode data structure.
-    #[derive(Debug)]
-    pub enum BincodeDecodeError {
-        /// An IO error occurred.
-        IO(std::io::Error),
-        /// A Bincode error occurred.
-        Decode(DecodeError),
-    }
-
-    impl From<std::io::Error> for BincodeDecodeError {
-        fn from(err: std::io::Error) -> Self {
-            Self::IO(err)
-        }
-    }
-
-    impl core::fmt::Display for BincodeDecodeError {
-        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-            match self {
-                Self::IO(err) => write!(f, "IO error: {}", err),
-                Self::Decode(err) => write!(f, "Decode error: {}", err),
-            }
-        }
-    }
-
-    impl std::error::Error for BincodeDecodeError {}
-
-    impl<D> Decoder for BincodeCodec<D>
-    where
-        D: Decode,
-    {
-        type Item = D;
-        type Error = BincodeDecodeError;
-
-        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-            if src.len() < SIZE_OF_LENGTH {
-                return Ok(None);
-            }
-
-            let payload_len = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
-
-            let packet_len = payload_len + SIZE_OF_LENGTH;
-
-            if src.len() < packet_len {
-                src.reserve(packet_len - src.len());
-
-                return Ok(None);
-            }
-
-            let (item, _) = bincode::decode_from_slice(
-                &src[SIZE_OF_LENGTH..packet_len],
-                bincode::config::standard(),
-            )
-            .map_err(BincodeDecodeError::Decode)?;
-
-            src.advance(packet_len);
-
-            Ok(Some(item))
-        }
-    }
-
-    /// An error that can occur when encoding a bincode data structure into a sequence of bytes with a payload length prefix.
-    #[derive(Debug)]
-    pub enum BincodeEncodeError {
-        /// An IO error occurred.
-        IO(std::io::Error),
-        /// A Bincode error occurred.
-        Encode(EncodeError),
-        /// The payload size is greater than u32::MAX.
-        PayloadTooLarge,
-    }
-
-    impl From<std::io::Error> for BincodeEncodeError {
-        fn from(err: std::io::Error) -> Self {
-            Self::IO(err)
-        }
-    }
-
-    impl core::fmt::Display for BincodeEncodeError {
-        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-            match self {
-                Self::IO(err) => write!(f, "IO error: {}", err),
-                Self::Encode(err) => write!(f, "Encode error: {}", err),
-                Self::PayloadTooLarge => write!(f, "Payload too large"),
-            }
-        }
-    }
-
-    impl std::error::Error for BincodeEncodeError {}
-
-    impl<D> Encoder<D> for BincodeCodec<D>
-    where
-        D: Encode,
-    {
-        type Error = BincodeEncodeError;
-
-        fn encode(&mut self, item: D, dst: &mut BytesMut) -> Result<(), Self::Error> {
-            let start_len = dst.len();
-
-            dst.put_u32(0);
-
-            let payload_len = bincode::encode_into_std_write(
-                item,
-                &mut dst.writer(),
-                bincode::config::standard(),
-            )
-            .map_err(BincodeEncodeError::Encode)?;
-
-            if payload_len > u32::MAX as usize {
-                return Err(BincodeEncodeError::PayloadTooLarge);
-            }
-
-            dst[start_len..start_len + SIZE_OF_LENGTH]
-                .copy_from_slice(&(payload_len as u32).to_be_bytes());
-
-            Ok(())
-        }
-    }
-}
-
 #[cfg(all(test, feature = "std", feature = "tokio"))]
 mod test {
     extern crate std;
@@ -322,7 +320,12 @@ mod test {
     use futures::{pin_mut, SinkExt, StreamExt};
     use tokio_util::codec::{FramedRead as TokioFramedRead, FramedWrite as TokioFramedWrite};
 
-    use crate::{sink_stream, test::init_tracing, tokio::Compat, FramedRead, FramedWrite};
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance, assert_roundtrip},
+        tokio::{Compat, TokioCodec},
+        FramedRead, FramedWrite, LengthFieldWidth,
+    };
 
     use super::*;
 
@@ -377,15 +380,129 @@ mod test {
     }
 
     #[tokio::test]
-    async fn sink_stream() {
+    async fn roundtrip() {
         init_tracing();
 
-        let items = test_messages();
+        assert_roundtrip::<1024, _, _, _>(
+            BincodeCodec::<BincodeMessage>::new(),
+            BincodeCodec::<BincodeMessage>::new(),
+            &test_messages(),
+        )
+        .await;
+    }
 
-        let decoder = BincodeCodec::<BincodeMessage>::new();
-        let encoder = BincodeCodec::<BincodeMessage>::new();
+    #[test]
+    fn conformance() {
+        init_tracing();
 
-        sink_stream!(encoder, decoder, items);
+        assert_codec_conformance::<1024, _, _, _>(
+            BincodeCodec::<BincodeMessage>::new(),
+            BincodeCodec::<BincodeMessage>::new(),
+            &test_messages(),
+        );
+    }
+
+    #[test]
+    fn configurable() {
+        init_tracing();
+
+        let config = bincode::config::standard()
+            .with_big_endian()
+            .with_fixed_int_encoding();
+
+        let length_codec = LengthDelimitedCodec::builder()
+            .length_field_width(LengthFieldWidth::Two)
+            .build();
+
+        let mut codec =
+            BincodeCodec::<u32, _>::new_with_config(config).with_length_codec(length_codec.clone());
+        let mut dst = [0_u8; 16];
+
+        let size = codec.encode(0x0102_0304, &mut dst).expect("Must encode");
+
+        assert_eq!(b"\x00\x04\x01\x02\x03\x04", &dst[..size]);
+        assert_eq!(
+            Some((0x0102_0304, size)),
+            codec.decode_owned(&mut dst[..size]).expect("Must decode")
+        );
+
+        assert_codec_conformance::<1024, _, _, _>(
+            BincodeCodec::<BincodeMessage, _>::new_with_config(config)
+                .with_length_codec(length_codec.clone()),
+            BincodeCodec::<BincodeMessage, _>::new_with_config(config)
+                .with_length_codec(length_codec),
+            &test_messages(),
+        );
+    }
+
+    #[test]
+    fn decode_limits() {
+        init_tracing();
+
+        let mut dst = [0_u8; 64];
+
+        let size = BincodeCodec::<BincodeMessage>::new()
+            .encode(test_messages().remove(2), &mut dst)
+            .expect("Must encode");
+
+        let mut codec = BincodeCodec::<BincodeMessage, _>::new_with_config(
+            bincode::config::standard().with_limit::<8>(),
+        );
+
+        assert!(matches!(
+            codec.decode_owned(&mut dst[..size]),
+            Err(BincodeDecodeError::Decode(DecodeError::LimitExceeded))
+        ));
+
+        let mut codec = BincodeCodec::<BincodeMessage>::new()
+            .with_length_codec(LengthDelimitedCodec::builder().max_frame_length(8).build());
+
+        assert!(matches!(
+            codec.decode_owned(&mut dst[..size]),
+            Err(BincodeDecodeError::Length(
+                LengthDelimitedDecodeError::FrameTooLarge
+            ))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "length field offset of 0")]
+    fn length_codec_with_head() {
+        let _ = BincodeCodec::<u32>::new().with_length_codec(
+            LengthDelimitedCodec::builder()
+                .length_field_offset(1)
+                .build(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "default num_skip")]
+    fn length_codec_keeping_the_length_field() {
+        let _ = BincodeCodec::<u32>::new()
+            .with_length_codec(LengthDelimitedCodec::builder().num_skip(0).build());
+    }
+
+    /// Not [`Clone`], larger than the default encode capacity of a [`TokioCodec`].
+    #[derive(Debug, PartialEq, bincode::Encode, bincode::Decode)]
+    struct LargeMessage(Vec<u8>);
+
+    #[test]
+    fn tokio_codec_by_reference() {
+        init_tracing();
+
+        let item = LargeMessage(std::vec![7; 4096]);
+
+        let mut codec = TokioCodec::new(BincodeCodec::<LargeMessage>::new());
+        let mut dst = tokio_util::bytes::BytesMut::new();
+
+        tokio_util::codec::Encoder::encode(&mut codec, &item, &mut dst).expect("Must encode");
+
+        let decoded = tokio_util::codec::Decoder::decode(&mut codec, &mut dst)
+            .expect("Must decode")
+            .expect("Must yield");
+
+        assert_eq!(item, decoded);
+        assert!(dst.is_empty());
     }
 
     macro_rules! collect_and_assert {
@@ -430,7 +547,10 @@ mod test {
             assert_eq!(test_messages(), collected);
         }
         {
-            let framer = TokioFramedRead::new($read_3, BincodeCodec::<BincodeMessage>::new());
+            let framer = TokioFramedRead::new(
+                $read_3,
+                TokioCodec::new(BincodeCodec::<BincodeMessage>::new()),
+            );
 
             let collected = framer
                 .collect::<Vec<_>>()
@@ -522,7 +642,10 @@ mod test {
             tokio_stream_write,
         ] {
             tokio::spawn(async move {
-                let mut sink = TokioFramedWrite::new(write, BincodeCodec::<BincodeMessage>::new());
+                let mut sink = TokioFramedWrite::new(
+                    write,
+                    TokioCodec::new(BincodeCodec::<BincodeMessage>::new()),
+                );
 
                 for item in test_messages() {
                     sink.send(item).await.expect("Must send");
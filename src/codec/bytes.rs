@@ -5,8 +5,8 @@ use core::convert::Infallible;
 use heapless::Vec;
 
 use crate::{
-    decode::{Decoder, DecoderOwned},
-    encode::Encoder,
+    decode::{Decoder, DecoderOwned, DecoderRef},
+    encode::{BufferTooSmallError, Encoder, EncoderEof},
 };
 
 /// A codec that decodes a sequence of bytes into a sequence of bytes and encodes a sequence of bytes into a sequence of bytes.
@@ -27,6 +27,20 @@ impl<'buf> Decoder<'buf> for BytesCodec {
     type Error = Infallible;
 
     fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for BytesCodec {
+    type Item = &'buf [u8];
+    type Error = Infallible;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        // An empty frame consumes nothing and would be yielded forever.
+        if src.is_empty() {
+            return Ok(None);
+        }
+
         Ok(Some((src, src.len())))
     }
 }
@@ -50,6 +64,12 @@ impl core::fmt::Display for BytesEncodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for BytesEncodeError {}
 
+impl BufferTooSmallError for BytesEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
 impl Encoder<&[u8]> for BytesCodec {
     type Error = BytesEncodeError;
 
@@ -66,6 +86,10 @@ impl Encoder<&[u8]> for BytesCodec {
     }
 }
 
+impl EncoderEof for BytesCodec {
+    type Error = BytesEncodeError;
+}
+
 /// An owned [`BytesCodec`].
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -105,6 +129,22 @@ impl<const N: usize> DecoderOwned for BytesCodecOwned<N> {
     }
 }
 
+impl<'buf, const N: usize> DecoderRef<'buf> for BytesCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = ();
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src) {
+            Ok(Some((bytes, size))) => {
+                let item = Vec::from_slice(bytes)?;
+                Ok(Some((item, size)))
+            }
+            Ok(None) => Ok(None),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 impl<const N: usize> Encoder<Vec<u8, N>> for BytesCodecOwned<N> {
     type Error = BytesEncodeError;
 
@@ -112,3 +152,38 @@ impl<const N: usize> Encoder<Vec<u8, N>> for BytesCodecOwned<N> {
         Encoder::encode(&mut self.inner, &item, dst)
     }
 }
+
+impl<const N: usize> EncoderEof for BytesCodecOwned<N> {
+    type Error = BytesEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use futures::StreamExt;
+
+    use crate::{test::init_tracing, testing::ChunkedReader, FramedRead, FramedReadError};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn framed_read() {
+        init_tracing();
+
+        let reader = ChunkedReader::new(b"Hello, world!", 5);
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(BytesCodecOwned::<16>::new(), reader, [0_u8; 16]);
+
+        let collected = framed_read.stream().collect::<Vec<_>>().await;
+
+        assert_eq!(4, collected.len());
+        assert!(matches!(collected[0], Ok(ref item) if item == b"Hello"));
+        assert!(matches!(collected[1], Ok(ref item) if item == b", wor"));
+        assert!(matches!(collected[2], Ok(ref item) if item == b"ld!"));
+        assert!(matches!(collected[3], Err(FramedReadError::EOF)));
+    }
+}
@@ -0,0 +1,574 @@
+//! Checksummed codec for wrapping any codec with a checksum trailer.
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof};
+
+/// An algorithm computing the checksum trailer of a [`Checksummed`] codec.
+pub trait ChecksumAlgorithm {
+    /// The size of the checksum in bytes, at most 4.
+    const SIZE: usize;
+
+    /// Computes the checksum of `bytes`.
+    fn checksum(&self, bytes: &[u8]) -> u32;
+}
+
+/// Computes a reflected CRC of `bytes` with the given reversed `polynomial`, `init` and `xor_out` values.
+#[inline]
+const fn reflected_crc(bytes: &[u8], polynomial: u32, init: u32, xor_out: u32) -> u32 {
+    let mut crc = init;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        crc ^= bytes[index] as u32;
+
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+
+            bit += 1;
+        }
+
+        index += 1;
+    }
+
+    crc ^ xor_out
+}
+
+/// Computes a CRC of `bytes` with the given `width`, `polynomial`, `init` and `xor_out` values, most significant bit first.
+#[inline]
+const fn crc(bytes: &[u8], width: u32, polynomial: u32, init: u32, xor_out: u32) -> u32 {
+    let top = 1 << (width - 1);
+    let mask = ((1_u64 << width) - 1) as u32;
+
+    let mut crc = init;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        crc ^= (bytes[index] as u32) << (width - 8);
+
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & top != 0 {
+                (crc << 1) ^ polynomial
+            } else {
+                crc << 1
+            };
+
+            bit += 1;
+        }
+
+        crc &= mask;
+        index += 1;
+    }
+
+    crc ^ xor_out
+}
+
+/// CRC-8/SMBUS, polynomial `0x07`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crc8;
+
+impl ChecksumAlgorithm for Crc8 {
+    const SIZE: usize = 1;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        crc(bytes, 8, 0x07, 0x00, 0x00)
+    }
+}
+
+/// CRC-16/CCITT-FALSE, polynomial `0x1021`, initial value `0xFFFF`.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crc16Ccitt;
+
+impl ChecksumAlgorithm for Crc16Ccitt {
+    const SIZE: usize = 2;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        crc(bytes, 16, 0x1021, 0xFFFF, 0x0000)
+    }
+}
+
+/// CRC-16/X.25, the frame check sequence of HDLC.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crc16X25;
+
+impl ChecksumAlgorithm for Crc16X25 {
+    const SIZE: usize = 2;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        reflected_crc(bytes, 0x8408, 0xFFFF, 0xFFFF)
+    }
+}
+
+/// CRC-16/MODBUS.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crc16Modbus;
+
+impl ChecksumAlgorithm for Crc16Modbus {
+    const SIZE: usize = 2;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        reflected_crc(bytes, 0xA001, 0xFFFF, 0x0000)
+    }
+}
+
+/// CRC-32 (ISO-HDLC), as used by Ethernet and zlib.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crc32;
+
+impl ChecksumAlgorithm for Crc32 {
+    const SIZE: usize = 4;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        reflected_crc(bytes, 0xEDB8_8320, 0xFFFF_FFFF, 0xFFFF_FFFF)
+    }
+}
+
+/// Fletcher-16.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fletcher16;
+
+impl ChecksumAlgorithm for Fletcher16 {
+    const SIZE: usize = 2;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        let (low, high) = bytes.iter().fold((0_u32, 0_u32), |(low, high), byte| {
+            let low = (low + *byte as u32) % 255;
+
+            (low, (high + low) % 255)
+        });
+
+        high << 8 | low
+    }
+}
+
+/// Adler-32.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Adler32;
+
+impl ChecksumAlgorithm for Adler32 {
+    const SIZE: usize = 4;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        const MOD: u32 = 65521;
+
+        let (low, high) = bytes.iter().fold((1_u32, 0_u32), |(low, high), byte| {
+            let low = (low + *byte as u32) % MOD;
+
+            (low, (high + low) % MOD)
+        });
+
+        high << 16 | low
+    }
+}
+
+/// The XOR of all bytes.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Xor8;
+
+impl ChecksumAlgorithm for Xor8 {
+    const SIZE: usize = 1;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        bytes
+            .iter()
+            .fold(0, |checksum, byte| checksum ^ *byte as u32)
+    }
+}
+
+/// A codec that wraps an inner codec, appending a big-endian checksum computed by algorithm `A` to every encoded frame and verifying and stripping it from every decoded frame.
+///
+/// The checksum covers the bytes of the inner frame as transmitted.
+/// Decoding borrowed items requires the inner codec to implement [`DecoderRef`], the bytes of the frame must not be modified before they are verified.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{Checksummed, Crc16Ccitt, DecoderRef, Encoder, LinesCodec};
+///
+/// let mut codec = Checksummed::new(LinesCodec::new(), Crc16Ccitt);
+/// let mut dst = [0_u8; 32];
+///
+/// let size = codec.encode(b"Hello", &mut dst).unwrap();
+///
+/// assert_eq!(size, 9);
+/// assert_eq!(
+///     codec.decode_ref(&dst[..size]).unwrap(),
+///     Some((&b"Hello"[..], 9))
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Checksummed<C, A> {
+    inner: C,
+    algorithm: A,
+}
+
+impl<C, A> Checksummed<C, A> {
+    /// Creates a new [`Checksummed`] codec wrapping `inner` with the given checksum `algorithm`.
+    #[inline]
+    pub const fn new(inner: C, algorithm: A) -> Self {
+        Self { inner, algorithm }
+    }
+
+    /// Returns reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the inner codec.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns reference to the checksum algorithm.
+    #[inline]
+    pub const fn algorithm(&self) -> &A {
+        &self.algorithm
+    }
+
+    /// Consumes the [`Checksummed`] codec and returns the inner codec.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, A: ChecksumAlgorithm> Checksummed<C, A> {
+    /// Verifies the checksum trailer following the inner frame of `size` bytes.
+    ///
+    /// Returns the size of the frame including the trailer, or `None` if the trailer is incomplete.
+    fn verify<E>(
+        &self,
+        src: &[u8],
+        size: usize,
+    ) -> Result<Option<usize>, ChecksummedDecodeError<E>> {
+        let frame_size = size + A::SIZE;
+
+        if src.len() < frame_size {
+            return Ok(None);
+        }
+
+        let expected = self.algorithm.checksum(&src[..size]);
+        let actual = src[size..frame_size]
+            .iter()
+            .fold(0_u32, |value, byte| value << 8 | *byte as u32);
+
+        if expected != actual {
+            return Err(ChecksummedDecodeError::Mismatch { expected, actual });
+        }
+
+        Ok(Some(frame_size))
+    }
+}
+
+/// An error that can occur when decoding a frame with a checksum trailer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChecksummedDecodeError<E> {
+    /// The inner codec failed to decode the frame.
+    Decode(E),
+    /// The checksum trailer does not match the frame.
+    Mismatch {
+        /// The checksum computed over the frame.
+        expected: u32,
+        /// The checksum found in the trailer.
+        actual: u32,
+    },
+}
+
+impl<E> core::fmt::Display for ChecksummedDecodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "Decode error: {}", err),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected {:#x}, actual {:#x}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for ChecksummedDecodeError<E> where E: core::fmt::Display + std::fmt::Debug
+{}
+
+impl<'buf, C, A> DecoderRef<'buf> for Checksummed<C, A>
+where
+    C: DecoderRef<'buf>,
+    A: ChecksumAlgorithm,
+{
+    type Item = C::Item;
+    type Error = ChecksummedDecodeError<C::Error>;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match self
+            .inner
+            .decode_ref(src)
+            .map_err(ChecksummedDecodeError::Decode)?
+        {
+            Some((item, size)) => Ok(self.verify(src, size)?.map(|size| (item, size))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof_ref(
+        &mut self,
+        src: &'buf [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match self
+            .inner
+            .decode_eof_ref(src)
+            .map_err(ChecksummedDecodeError::Decode)?
+        {
+            Some((item, size)) => Ok(self.verify(src, size)?.map(|size| (item, size))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'buf, C, A> Decoder<'buf> for Checksummed<C, A>
+where
+    C: DecoderRef<'buf>,
+    A: ChecksumAlgorithm,
+{
+    type Item = C::Item;
+    type Error = ChecksummedDecodeError<C::Error>;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+
+    fn decode_eof(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_eof_ref(src)
+    }
+}
+
+impl<C, A, T, E> DecoderOwned for Checksummed<C, A>
+where
+    C: for<'buf> DecoderRef<'buf, Item = T, Error = E>,
+    A: ChecksumAlgorithm,
+{
+    type Item = T;
+    type Error = ChecksummedDecodeError<E>;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+
+    fn decode_eof_owned(
+        &mut self,
+        src: &mut [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_eof_ref(src)
+    }
+}
+
+/// An error that can occur when encoding a frame with a checksum trailer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChecksummedEncodeError<E> {
+    /// The inner codec failed to encode the frame.
+    Encode(E),
+    /// The input buffer is too small to fit the checksum trailer.
+    BufferTooSmall,
+}
+
+impl<E> core::fmt::Display for ChecksummedEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Encode(err) => write!(f, "Encode error: {}", err),
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for ChecksummedEncodeError<E> where E: core::fmt::Display + std::fmt::Debug
+{}
+
+impl<E> BufferTooSmallError for ChecksummedEncodeError<E>
+where
+    E: BufferTooSmallError,
+{
+    fn is_buffer_too_small(&self) -> bool {
+        match self {
+            Self::BufferTooSmall => true,
+            Self::Encode(err) => err.is_buffer_too_small(),
+        }
+    }
+}
+
+impl<C, A> Checksummed<C, A>
+where
+    A: ChecksumAlgorithm,
+{
+    /// Appends the checksum of the inner frame of `size` bytes to `dst`.
+    ///
+    /// Empty frames are left untouched.
+    fn append<E>(&self, dst: &mut [u8], size: usize) -> Result<usize, ChecksummedEncodeError<E>> {
+        if size == 0 {
+            return Ok(0);
+        }
+
+        let frame_size = size + A::SIZE;
+
+        if dst.len() < frame_size {
+            return Err(ChecksummedEncodeError::BufferTooSmall);
+        }
+
+        let checksum = self.algorithm.checksum(&dst[..size]).to_be_bytes();
+
+        dst[size..frame_size].copy_from_slice(&checksum[checksum.len() - A::SIZE..]);
+
+        Ok(frame_size)
+    }
+}
+
+impl<I, C, A> Encoder<I> for Checksummed<C, A>
+where
+    C: Encoder<I>,
+    A: ChecksumAlgorithm,
+{
+    type Error = ChecksummedEncodeError<C::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let size = self
+            .inner
+            .encode(item, dst)
+            .map_err(ChecksummedEncodeError::Encode)?;
+
+        self.append(dst, size)
+    }
+}
+
+impl<C, A> EncoderEof for Checksummed<C, A>
+where
+    C: EncoderEof,
+    A: ChecksumAlgorithm,
+{
+    type Error = ChecksummedEncodeError<C::Error>;
+
+    fn encode_eof(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let size = self
+            .inner
+            .encode_eof(dst)
+            .map_err(ChecksummedEncodeError::Encode)?;
+
+        // No closing frame, no checksum.
+        if size == 0 {
+            return Ok(0);
+        }
+
+        self.append(dst, size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+        LengthCodecOwned, LinesCodec, LinesCodecOwned,
+    };
+
+    use super::*;
+
+    #[test]
+    fn check_values() {
+        let input = b"123456789";
+
+        assert_eq!(0xF4, Crc8.checksum(input));
+        assert_eq!(0x29B1, Crc16Ccitt.checksum(input));
+        assert_eq!(0x906E, Crc16X25.checksum(input));
+        assert_eq!(0x4B37, Crc16Modbus.checksum(input));
+        assert_eq!(0xCBF4_3926, Crc32.checksum(input));
+        assert_eq!(0x1EDE, Fletcher16.checksum(input));
+        assert_eq!(0x31, Xor8.checksum(input));
+
+        assert_eq!(0xC8F0, Fletcher16.checksum(b"abcde"));
+        assert_eq!(0x11E6_0398, Adler32.checksum(b"Wikipedia"));
+    }
+
+    #[test]
+    fn mismatch() {
+        init_tracing();
+
+        let mut codec = Checksummed::new(LinesCodec::new(), Crc16Ccitt);
+        let expected = Crc16Ccitt.checksum(b"Hello\n");
+
+        assert_eq!(
+            Err(ChecksummedDecodeError::Mismatch {
+                expected,
+                actual: 0x1234
+            }),
+            codec.decode_ref(b"Hello\n\x12\x34")
+        );
+
+        // The trailer is awaited.
+        assert_eq!(Ok(None), codec.decode_ref(b"Hello\n\x12"));
+
+        let mut dst = [0_u8; 7];
+
+        assert!(matches!(
+            codec.encode(b"Hello", &mut dst),
+            Err(ChecksummedEncodeError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        assert_codec_conformance_items::<32, _>(
+            Checksummed::new(LengthCodecOwned::<32>::new(), Crc32),
+            &[b""],
+        );
+
+        assert_codec_conformance_items::<32, _>(
+            Checksummed::new(LengthCodecOwned::<32>::new(), Fletcher16),
+            &[b""],
+        );
+
+        let input = [
+            &b"Hello\n"[..],
+            &Crc8.checksum(b"Hello\n").to_be_bytes()[3..],
+            b"How",
+        ]
+        .concat();
+        let expected: &[&[u8]] = &[b"Hello"];
+
+        assert_decoder_conformance_truncated(
+            Checksummed::new(LinesCodecOwned::<32>::new(), Crc8),
+            &input,
+            expected,
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! COBS (Consistent Overhead Byte Stuffing) codec for encoding and decoding bytes delimited by a zero byte.
+
+use heapless::Vec;
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, Encoder, EncoderEof};
+
+/// The byte that delimits COBS frames.
+pub const COBS_DELIMITER: u8 = 0x00;
+
+/// The largest code byte, a block of 254 non-zero bytes without an implied zero.
+const MAX_CODE: u8 = 0xFF;
+
+/// Returns the worst-case size of the COBS encoded `len` bytes, excluding the delimiter.
+///
+/// The overhead is one code byte per started block of 254 bytes, and at least one.
+#[inline]
+pub const fn cobs_max_encoded_len(len: usize) -> usize {
+    let blocks = (len + MAX_CODE as usize - 2) / (MAX_CODE as usize - 1);
+
+    len + if blocks == 0 { 1 } else { blocks }
+}
+
+/// A codec that decodes a sequence of COBS encoded bytes ending with a zero byte into a sequence of bytes and encodes a sequence of bytes into a sequence of COBS encoded bytes ending with a zero byte.
+///
+/// Decoding happens in place inside the buffer given to [`Decoder::decode`], the returned item borrows the unstuffed bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{CobsCodec, Decoder};
+///
+/// let mut codec = CobsCodec::new();
+/// let mut src = *b"\x03ab\x02c\x00";
+///
+/// assert_eq!(
+///     codec.decode(&mut src).unwrap(),
+///     Some((&b"ab\x00c"[..], 6))
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CobsCodec {
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+}
+
+impl CobsCodec {
+    /// Creates a new [`CobsCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { seen: 0 }
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.seen = 0;
+    }
+}
+
+/// An error that can occur when decoding a sequence of COBS encoded bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CobsDecodeError {
+    /// A delimiter was found without a preceding frame.
+    EmptyFrame,
+    /// A code byte points past the end of the frame.
+    Truncated,
+    /// The decoded payload does not fit into the item of a [`CobsCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for CobsDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptyFrame => write!(f, "Empty frame"),
+            Self::Truncated => write!(f, "Truncated frame"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CobsDecodeError {}
+
+/// Unstuffs the COBS encoded `frame` in place, returning the length of the decoded bytes.
+///
+/// `frame` must not contain the delimiter.
+fn unstuff(frame: &mut [u8]) -> Result<usize, CobsDecodeError> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < frame.len() {
+        let code = frame[read] as usize;
+        let block_end = read + code;
+
+        if block_end > frame.len() {
+            return Err(CobsDecodeError::Truncated);
+        }
+
+        // The write index never passes the read index, every block drops its code byte.
+        frame.copy_within(read + 1..block_end, write);
+
+        write += code - 1;
+        read = block_end;
+
+        if code != MAX_CODE as usize && read < frame.len() {
+            frame[write] = COBS_DELIMITER;
+            write += 1;
+        }
+    }
+
+    Ok(write)
+}
+
+impl<'buf> Decoder<'buf> for CobsCodec {
+    type Item = &'buf [u8];
+    type Error = CobsDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        while self.seen < src.len() {
+            if src[self.seen] != COBS_DELIMITER {
+                self.seen += 1;
+
+                continue;
+            }
+
+            let frame_len = self.seen;
+
+            self.seen = 0;
+
+            if frame_len == 0 {
+                return Err(CobsDecodeError::EmptyFrame);
+            }
+
+            let len = unstuff(&mut src[..frame_len])?;
+            let src: &'buf [u8] = src;
+
+            return Ok(Some((&src[..len], frame_len + 1)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of COBS encoded bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CobsEncodeError {
+    /// The input buffer is too small to fit the worst-case encoding of the sequence of bytes.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for CobsEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CobsEncodeError {}
+
+impl BufferTooSmallError for CobsEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl Encoder<&[u8]> for CobsCodec {
+    type Error = CobsEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        if dst.len() < cobs_max_encoded_len(item.len()) + 1 {
+            return Err(CobsEncodeError::BufferTooSmall);
+        }
+
+        let mut code_index = 0;
+        let mut write = 1;
+        let mut code = 1;
+
+        for (index, byte) in item.iter().enumerate() {
+            if *byte == COBS_DELIMITER {
+                dst[code_index] = code;
+                code_index = write;
+                write += 1;
+                code = 1;
+
+                continue;
+            }
+
+            dst[write] = *byte;
+            write += 1;
+            code += 1;
+
+            // A full block only starts a new one if more bytes follow.
+            if code == MAX_CODE && index + 1 < item.len() {
+                dst[code_index] = code;
+                code_index = write;
+                write += 1;
+                code = 1;
+            }
+        }
+
+        dst[code_index] = code;
+        dst[write] = COBS_DELIMITER;
+
+        Ok(write + 1)
+    }
+}
+
+impl EncoderEof for CobsCodec {
+    type Error = CobsEncodeError;
+}
+
+/// An owned [`CobsCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CobsCodecOwned<const N: usize> {
+    inner: CobsCodec,
+}
+
+impl<const N: usize> CobsCodecOwned<N> {
+    /// Creates a new [`CobsCodecOwned`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: CobsCodec::new(),
+        }
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.inner.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.seen = 0;
+    }
+}
+
+impl<const N: usize> From<CobsCodec> for CobsCodecOwned<N> {
+    fn from(inner: CobsCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for CobsCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = CobsDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match Decoder::decode(&mut self.inner, src)? {
+            Some((bytes, size)) => {
+                let item = Vec::from_slice(bytes).map_err(|_| CobsDecodeError::Capacity)?;
+
+                Ok(Some((item, size)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for CobsCodecOwned<N> {
+    type Error = CobsEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for CobsCodecOwned<N> {
+    type Error = CobsEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+    };
+
+    use super::*;
+
+    fn encode(item: &[u8]) -> Vec<u8> {
+        let mut dst = std::vec![0; cobs_max_encoded_len(item.len()) + 1];
+        let size = CobsCodec::new().encode(item, &mut dst).expect("Must fit");
+
+        dst.truncate(size);
+        dst
+    }
+
+    fn block(start: u8, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| start.wrapping_add(i as u8).max(1))
+            .collect()
+    }
+
+    #[test]
+    fn encode_vectors() {
+        assert_eq!(b"\x01\x00", &encode(b"")[..]);
+        assert_eq!(b"\x01\x01\x00", &encode(b"\x00")[..]);
+        assert_eq!(b"\x01\x01\x01\x00", &encode(b"\x00\x00")[..]);
+        assert_eq!(
+            b"\x03\x11\x22\x02\x33\x00",
+            &encode(b"\x11\x22\x00\x33")[..]
+        );
+        assert_eq!(
+            b"\x02\x11\x01\x01\x01\x00",
+            &encode(b"\x11\x00\x00\x00")[..]
+        );
+
+        let bytes = block(1, 254);
+        let encoded = encode(&bytes);
+
+        assert_eq!(cobs_max_encoded_len(254) + 1, encoded.len());
+        assert_eq!([&[0xFF][..], &bytes, &[0x00]].concat(), encoded);
+
+        let bytes = block(1, 255);
+        let encoded = encode(&bytes);
+
+        assert_eq!(cobs_max_encoded_len(255) + 1, encoded.len());
+        assert_eq!(
+            [&[0xFF][..], &bytes[..254], &[0x02, bytes[254], 0x00]].concat(),
+            encoded
+        );
+
+        let bytes = [&block(1, 254)[..], &[0x00]].concat();
+
+        assert_eq!(
+            [&[0xFF][..], &bytes[..254], &[0x01, 0x01, 0x00]].concat(),
+            encode(&bytes)
+        );
+    }
+
+    #[test]
+    fn decode_in_place() {
+        init_tracing();
+
+        let mut codec = CobsCodec::new();
+
+        for item in [
+            &b""[..],
+            b"\x00",
+            b"\x00\x00",
+            b"\x11\x22\x00\x33",
+            b"\x11\x00\x00\x00",
+            &block(1, 254),
+            &block(1, 255),
+            &[&block(1, 254)[..], &[0x00]].concat(),
+            &block(0, 1024),
+        ] {
+            let mut src = encode(item);
+            let len = src.len();
+
+            assert_eq!(Ok(Some((item, len))), codec.decode(&mut src));
+        }
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        assert_codec_conformance_items::<512, _>(
+            CobsCodecOwned::<512>::new(),
+            &[b"\x00\x01\x00", b"", &block(1, 300), b"Hey\x00"],
+        );
+
+        let input = b"\x06Hello\x00\x04Hey\x01\x00\x04How";
+        let expected: &[&[u8]] = &[b"Hello", b"Hey\x00"];
+
+        assert_decoder_conformance_truncated(CobsCodecOwned::<32>::new(), input, expected);
+    }
+
+    #[test]
+    fn malformed_input() {
+        init_tracing();
+
+        let mut codec = CobsCodec::new();
+
+        assert_eq!(Ok(None), codec.decode(&mut { *b"\x05abc" }));
+        assert_eq!(
+            Err(CobsDecodeError::Truncated),
+            codec.decode(&mut { *b"\x05abc\x00" })
+        );
+        assert_eq!(
+            Err(CobsDecodeError::EmptyFrame),
+            codec.decode(&mut { *b"\x00" })
+        );
+
+        // The codec recovers at the next delimiter.
+        assert_eq!(
+            Ok(Some((&b"ab"[..], 4))),
+            codec.decode(&mut { *b"\x03ab\x00" })
+        );
+
+        let mut codec = CobsCodecOwned::<2>::new();
+
+        assert_eq!(
+            Err(CobsDecodeError::Capacity),
+            codec.decode_owned(&mut { *b"\x04abc\x00" })
+        );
+
+        let mut dst = [0_u8; 5];
+
+        assert_eq!(
+            Err(CobsEncodeError::BufferTooSmall),
+            CobsCodec::new().encode(b"abcd", &mut dst)
+        );
+    }
+}
@@ -0,0 +1,558 @@
+//! HDLC-like (RFC 1662) codec for encoding and decoding byte stuffed frames delimited by flag bytes and protected by a frame check sequence.
+
+use heapless::Vec;
+
+use crate::{
+    BufferTooSmallError, ChecksumAlgorithm, Crc16X25, Crc32, Decoder, DecoderOwned, Encoder,
+    EncoderEof,
+};
+
+/// The byte that delimits HDLC frames.
+pub const HDLC_FLAG: u8 = 0x7E;
+
+/// The byte that starts an HDLC escape sequence.
+pub const HDLC_ESCAPE: u8 = 0x7D;
+
+/// The value escaped bytes are XORed with.
+const ESCAPE_XOR: u8 = 0x20;
+
+/// The frame check sequence of an HDLC frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HdlcFcs {
+    /// 16-bit CRC-16/X.25.
+    #[default]
+    Crc16,
+    /// 32-bit CRC-32.
+    Crc32,
+}
+
+impl HdlcFcs {
+    /// Returns the size of the frame check sequence in bytes.
+    #[inline]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Crc16 => 2,
+            Self::Crc32 => 4,
+        }
+    }
+
+    /// Computes the frame check sequence of `bytes`, in transmission order.
+    fn compute(&self, bytes: &[u8]) -> [u8; 4] {
+        let fcs = match self {
+            Self::Crc16 => Crc16X25.checksum(bytes),
+            Self::Crc32 => Crc32.checksum(bytes),
+        };
+
+        fcs.to_le_bytes()
+    }
+}
+
+/// A codec that decodes a sequence of HDLC framed bytes into a sequence of bytes and encodes a sequence of bytes into a sequence of HDLC framed bytes.
+///
+/// Decoding happens in place inside the buffer given to [`Decoder::decode`], the returned item borrows the unstuffed bytes without the frame check sequence.
+/// Leading flags and empty frames are skipped, they are consumed together with the next frame.
+///
+/// A frame that fails to decode is skipped by the next call with the same buffer.
+/// A [`FramedRead`](crate::FramedRead) can therefore keep reading after a [`HdlcDecodeError::Fcs`] error.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{Decoder, Encoder, HdlcCodec, HdlcFcs};
+///
+/// let mut codec = HdlcCodec::new(HdlcFcs::Crc16).with_accm(0xFFFF_FFFF);
+/// let mut dst = [0_u8; 32];
+///
+/// let size = codec.encode(b"\x01\x7E", &mut dst).unwrap();
+///
+/// assert_eq!(
+///     codec.decode(&mut dst[..size]).unwrap(),
+///     Some((&b"\x01\x7E"[..], size))
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HdlcCodec {
+    /// The frame check sequence.
+    fcs: HdlcFcs,
+    /// The async control character map, bit `n` set escapes the byte `n` when encoding.
+    accm: u32,
+    /// The number of bytes before the current frame.
+    start: usize,
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+}
+
+impl HdlcCodec {
+    /// Creates a new [`HdlcCodec`] with the given frame check sequence and an empty async control character map.
+    #[inline]
+    pub const fn new(fcs: HdlcFcs) -> Self {
+        Self {
+            fcs,
+            accm: 0,
+            start: 0,
+            seen: 0,
+        }
+    }
+
+    /// Sets the async control character map applied when encoding.
+    ///
+    /// Bit `n` set escapes the control character `n`.
+    #[inline]
+    pub const fn with_accm(mut self, accm: u32) -> Self {
+        self.accm = accm;
+        self
+    }
+
+    /// Returns the frame check sequence.
+    #[inline]
+    pub const fn fcs(&self) -> HdlcFcs {
+        self.fcs
+    }
+
+    /// Returns the async control character map applied when encoding.
+    #[inline]
+    pub const fn accm(&self) -> u32 {
+        self.accm
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.seen = 0;
+    }
+
+    /// Skips the first `size` bytes of the next slice.
+    #[inline]
+    fn skip(&mut self, size: usize) {
+        self.start = size;
+        self.seen = size;
+    }
+
+    /// Returns whether `byte` must be escaped when encoding.
+    #[inline]
+    const fn must_escape(&self, byte: u8) -> bool {
+        match byte {
+            HDLC_FLAG | HDLC_ESCAPE => true,
+            byte if byte < 0x20 => self.accm & (1 << byte) != 0,
+            _ => false,
+        }
+    }
+}
+
+/// An error that can occur when decoding a sequence of HDLC framed bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HdlcDecodeError {
+    /// An escape byte ends the frame.
+    InvalidEscape,
+    /// The frame is shorter than the frame check sequence.
+    FrameTooShort,
+    /// The frame check sequence does not match the frame.
+    Fcs,
+    /// The decoded payload does not fit into the item of a [`HdlcCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for HdlcDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidEscape => write!(f, "Invalid escape sequence"),
+            Self::FrameTooShort => write!(f, "Frame too short"),
+            Self::Fcs => write!(f, "Frame check sequence mismatch"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HdlcDecodeError {}
+
+/// Unstuffs the HDLC framed `frame` in place, returning the length of the decoded bytes.
+///
+/// `frame` must not contain the flag byte.
+fn unstuff(frame: &mut [u8]) -> Result<usize, HdlcDecodeError> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < frame.len() {
+        let byte = match frame[read] {
+            HDLC_ESCAPE => {
+                read += 1;
+
+                match frame.get(read) {
+                    Some(byte) => byte ^ ESCAPE_XOR,
+                    None => return Err(HdlcDecodeError::InvalidEscape),
+                }
+            }
+            byte => byte,
+        };
+
+        frame[write] = byte;
+        read += 1;
+        write += 1;
+    }
+
+    Ok(write)
+}
+
+impl<'buf> Decoder<'buf> for HdlcCodec {
+    type Item = &'buf [u8];
+    type Error = HdlcDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        while self.seen < src.len() {
+            if src[self.seen] != HDLC_FLAG {
+                self.seen += 1;
+
+                continue;
+            }
+
+            // A leading flag or an empty frame.
+            if self.seen == self.start {
+                self.start += 1;
+                self.seen += 1;
+
+                continue;
+            }
+
+            let start = self.start;
+            let end = self.seen;
+
+            // The frame is consumed with the next one if it fails to decode.
+            self.skip(end + 1);
+
+            let len = unstuff(&mut src[start..end])?;
+
+            let fcs_size = self.fcs.size();
+
+            if len < fcs_size {
+                return Err(HdlcDecodeError::FrameTooShort);
+            }
+
+            let (payload, fcs) = src[start..start + len].split_at(len - fcs_size);
+
+            if self.fcs.compute(payload)[..fcs_size] != *fcs {
+                return Err(HdlcDecodeError::Fcs);
+            }
+
+            self.clear();
+
+            let src: &'buf [u8] = src;
+
+            return Ok(Some((&src[start..start + len - fcs_size], end + 1)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of HDLC framed bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HdlcEncodeError {
+    /// The input buffer is too small to fit the encoded sequence of bytes.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for HdlcEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HdlcEncodeError {}
+
+impl BufferTooSmallError for HdlcEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl Encoder<&[u8]> for HdlcCodec {
+    type Error = HdlcEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let fcs = self.fcs.compute(item);
+        let fcs = &fcs[..self.fcs.size()];
+
+        let size = 2 + item
+            .iter()
+            .chain(fcs)
+            .map(|byte| if self.must_escape(*byte) { 2 } else { 1 })
+            .sum::<usize>();
+
+        if dst.len() < size {
+            return Err(HdlcEncodeError::BufferTooSmall);
+        }
+
+        dst[0] = HDLC_FLAG;
+
+        let mut index = 1;
+
+        for byte in item.iter().chain(fcs) {
+            if self.must_escape(*byte) {
+                dst[index] = HDLC_ESCAPE;
+                dst[index + 1] = byte ^ ESCAPE_XOR;
+                index += 2;
+            } else {
+                dst[index] = *byte;
+                index += 1;
+            }
+        }
+
+        dst[index] = HDLC_FLAG;
+
+        Ok(index + 1)
+    }
+}
+
+impl EncoderEof for HdlcCodec {
+    type Error = HdlcEncodeError;
+}
+
+/// An owned [`HdlcCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HdlcCodecOwned<const N: usize> {
+    inner: HdlcCodec,
+}
+
+impl<const N: usize> HdlcCodecOwned<N> {
+    /// Creates a new [`HdlcCodecOwned`] with the given frame check sequence and an empty async control character map.
+    #[inline]
+    pub const fn new(fcs: HdlcFcs) -> Self {
+        Self {
+            inner: HdlcCodec::new(fcs),
+        }
+    }
+
+    /// Sets the async control character map applied when encoding.
+    ///
+    /// Bit `n` set escapes the control character `n`.
+    #[inline]
+    pub const fn with_accm(mut self, accm: u32) -> Self {
+        self.inner.accm = accm;
+        self
+    }
+
+    /// Returns the frame check sequence.
+    #[inline]
+    pub const fn fcs(&self) -> HdlcFcs {
+        self.inner.fcs
+    }
+
+    /// Returns the async control character map applied when encoding.
+    #[inline]
+    pub const fn accm(&self) -> u32 {
+        self.inner.accm
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.inner.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+impl<const N: usize> From<HdlcCodec> for HdlcCodecOwned<N> {
+    fn from(inner: HdlcCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for HdlcCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = HdlcDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match Decoder::decode(&mut self.inner, src)? {
+            Some((bytes, size)) => match Vec::from_slice(bytes) {
+                Ok(item) => Ok(Some((item, size))),
+                Err(_) => {
+                    self.inner.skip(size);
+
+                    Err(HdlcDecodeError::Capacity)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for HdlcCodecOwned<N> {
+    type Error = HdlcEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for HdlcCodecOwned<N> {
+    type Error = HdlcEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use crate::{
+        test::init_tracing,
+        testing::{
+            assert_codec_conformance, assert_decoder_conformance_truncated, conformance_items,
+        },
+        FramedRead, FramedReadError,
+    };
+
+    use super::*;
+
+    #[test]
+    fn fcs_check_values() {
+        assert_eq!([0x6E, 0x90, 0, 0], HdlcFcs::Crc16.compute(b"123456789"));
+        assert_eq!(
+            0xCBF4_3926_u32.to_le_bytes(),
+            HdlcFcs::Crc32.compute(b"123456789")
+        );
+    }
+
+    #[test]
+    fn encode_with_accm() {
+        let mut dst = [0_u8; 32];
+
+        let mut codec = HdlcCodec::new(HdlcFcs::Crc16);
+        let size = codec.encode(b"\x7E\x01\x7D", &mut dst).unwrap();
+        let fcs = HdlcFcs::Crc16.compute(b"\x7E\x01\x7D");
+
+        assert_eq!(
+            [&b"\x7E\x7D\x5E\x01\x7D\x5D"[..], &fcs[..2], b"\x7E"].concat(),
+            &dst[..size]
+        );
+
+        let mut codec = HdlcCodec::new(HdlcFcs::Crc16).with_accm(1 << 0x01);
+        let size = codec.encode(b"\x7E\x01\x7D", &mut dst).unwrap();
+
+        assert_eq!(
+            [&b"\x7E\x7D\x5E\x7D\x21\x7D\x5D"[..], &fcs[..2], b"\x7E"].concat(),
+            &dst[..size]
+        );
+
+        assert_eq!(
+            Err(HdlcEncodeError::BufferTooSmall),
+            codec.encode(b"\x7E\x01\x7D", &mut dst[..size - 1])
+        );
+    }
+
+    #[test]
+    fn malformed_frames_are_skipped() {
+        init_tracing();
+
+        let mut dst = [0_u8; 64];
+        let mut codec = HdlcCodec::new(HdlcFcs::Crc32);
+
+        let first = codec.encode(b"Hello", &mut dst).unwrap();
+        let second = codec.encode(b"Hey", &mut dst[first..]).unwrap();
+
+        // Corrupt a payload byte of the first frame.
+        dst[2] ^= 0x01;
+
+        let src = &mut dst[..first + second];
+
+        assert_eq!(Err(HdlcDecodeError::Fcs), codec.decode(src));
+        assert_eq!(Ok(Some((&b"Hey"[..], first + second))), codec.decode(src));
+
+        let mut codec = HdlcCodec::new(HdlcFcs::Crc16);
+        let mut src = *b"\x7E\x01\x7E\x02\x7D\x7E\x7E\x03";
+
+        assert_eq!(Err(HdlcDecodeError::FrameTooShort), codec.decode(&mut src));
+        assert_eq!(Err(HdlcDecodeError::InvalidEscape), codec.decode(&mut src));
+        assert_eq!(Ok(None), codec.decode(&mut src));
+    }
+
+    #[tokio::test]
+    async fn framed_read_keeps_reading_after_fcs_error() {
+        init_tracing();
+
+        let mut dst = [0_u8; 64];
+        let mut codec = HdlcCodec::new(HdlcFcs::Crc16);
+
+        let first = codec.encode(b"Hello", &mut dst).unwrap();
+        let mut size = first;
+
+        for item in [&b"Bad"[..], b"Hey"] {
+            size += codec.encode(item, &mut dst[size..]).unwrap();
+        }
+
+        // Corrupt the payload of the second frame.
+        dst[first + 1] ^= 0x01;
+
+        let mut framed_read = FramedRead::new_with_buffer(codec, &dst[..size], [0_u8; 32]);
+
+        let mut items = Vec::new();
+
+        loop {
+            match framed_read.read_frame().await {
+                Ok(Some(item)) => items.push(item.to_vec()),
+                Ok(None) => {}
+                Err(FramedReadError::Decode(HdlcDecodeError::Fcs)) => items.push(b"!".to_vec()),
+                Err(FramedReadError::EOF) => break,
+                Err(err) => panic!("Unexpected error: {err:?}"),
+            }
+        }
+
+        assert_eq!(
+            std::vec![b"Hello".to_vec(), b"!".to_vec(), b"Hey".to_vec()],
+            items
+        );
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        let items = conformance_items::<32>(&[b"\x7E\x7D\x00\x1F", b""]);
+
+        for fcs in [HdlcFcs::Crc16, HdlcFcs::Crc32] {
+            for accm in [0, 0xFFFF_FFFF] {
+                assert_codec_conformance::<1024, _, _, _>(
+                    HdlcCodecOwned::<32>::new(fcs).with_accm(accm),
+                    HdlcCodecOwned::<32>::new(fcs),
+                    &items,
+                );
+            }
+        }
+
+        let mut input = [0_u8; 64];
+        let size = HdlcCodec::new(HdlcFcs::Crc16)
+            .encode(b"Hello", &mut input)
+            .unwrap();
+
+        input[size..size + 3].copy_from_slice(b"How");
+
+        let expected: &[&[u8]] = &[b"Hello"];
+
+        assert_decoder_conformance_truncated(
+            HdlcCodecOwned::<32>::new(HdlcFcs::Crc16),
+            &input[..size + 3],
+            expected,
+        );
+    }
+}
@@ -4,7 +4,7 @@ use core::convert::Infallible;
 
 use heapless::Vec;
 
-use crate::{Decoder, DecoderOwned, Encoder};
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof};
 
 /// The size of the payload length prefix in bytes.
 pub const SIZE_OF_LENGTH: usize = core::mem::size_of::<u32>();
@@ -27,13 +27,23 @@ impl<'buf> Decoder<'buf> for LengthCodec {
     type Error = Infallible;
 
     fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for LengthCodec {
+    type Item = &'buf [u8];
+    type Error = Infallible;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
         if src.len() < SIZE_OF_LENGTH {
             return Ok(None);
         }
 
         let payload_len = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
 
-        let packet_len = payload_len + SIZE_OF_LENGTH;
+        // Saturates on 32-bit targets, where a payload length close to u32::MAX can never fit in the source anyway.
+        let packet_len = payload_len.saturating_add(SIZE_OF_LENGTH);
 
         if src.len() < packet_len {
             return Ok(None);
@@ -70,6 +80,12 @@ impl core::fmt::Display for LengthEncodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for LengthEncodeError {}
 
+impl BufferTooSmallError for LengthEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
 impl Encoder<&[u8]> for LengthCodec {
     type Error = LengthEncodeError;
 
@@ -93,6 +109,10 @@ impl Encoder<&[u8]> for LengthCodec {
     }
 }
 
+impl EncoderEof for LengthCodec {
+    type Error = LengthEncodeError;
+}
+
 /// An owned [`LengthCodec`].
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -132,6 +152,22 @@ impl<const N: usize> DecoderOwned for LengthCodecOwned<N> {
     }
 }
 
+impl<'buf, const N: usize> DecoderRef<'buf> for LengthCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = ();
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src) {
+            Ok(Some((bytes, size))) => {
+                let item = Vec::from_slice(bytes)?;
+                Ok(Some((item, size)))
+            }
+            Ok(None) => Ok(None),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 impl<const N: usize> Encoder<Vec<u8, N>> for LengthCodecOwned<N> {
     type Error = LengthEncodeError;
 
@@ -140,18 +176,21 @@ impl<const N: usize> Encoder<Vec<u8, N>> for LengthCodecOwned<N> {
     }
 }
 
+impl<const N: usize> EncoderEof for LengthCodecOwned<N> {
+    type Error = LengthEncodeError;
+}
+
 #[cfg(test)]
 mod test {
     extern crate std;
 
-    use std::vec::Vec;
-
-    use futures::{pin_mut, SinkExt, StreamExt};
-    use tokio::io::AsyncWriteExt;
-
     use crate::{
-        error, framed_read, sink_stream, test::init_tracing, tokio::Compat, FramedRead,
-        FramedReadError, FramedWrite,
+        test::init_tracing,
+        testing::{
+            assert_codec_conformance_items, assert_decode_buffer_sizes, assert_decode_splits_owned,
+            assert_decoder_conformance_truncated, conformance_items, CONFORMANCE_ITEMS,
+        },
+        FramedReadError,
     };
 
     use super::*;
@@ -160,68 +199,45 @@ mod test {
     async fn framed_read() {
         init_tracing();
 
-        let items: &[&[u8]] = &[
-            b"\x00\x00\x00\x05Hel",
-            b"lo\x00\x00",
-            b"\x00\x0DHell",
-            b"o, world!",
-            b"\x00",
-            b"\x00\x00\x03H",
-            b"ei\x00\x00",
-            b"\x00",
-            b"\x03sup",
-            b"\x00\x00",
-            b"\x00\x03Hey",
-            b"\x00\x00",
-            b"\x00\x0CHow are y",
-        ];
-
-        let decoder = LengthCodec::new();
-
-        let expected: &[&[u8]] = &[];
-        framed_read!(items, expected, decoder, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 4, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 4, BufferTooSmall);
-
-        let expected: &[&[u8]] = &[b"Hello"];
-        framed_read!(items, expected, decoder, 12, BufferTooSmall);
-
-        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
-        framed_read!(items, expected, decoder, 32, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 1, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 2, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 32, 4, BytesRemainingOnStream);
-
-        framed_read!(items, expected, decoder);
+        let input = b"\x00\x00\x00\x05Hello\x00\x00\x00\x0DHello, world!\x00\x00\x00\x03Hei\x00\x00\x00\x03sup\x00\x00\x00\x03Hey\x00\x00\x00\x0CHow are y";
+
+        assert_decode_buffer_sizes!(LengthCodec::new(), input, &[], BufferTooSmall, [1, 2, 4]);
+
+        assert_decode_buffer_sizes!(LengthCodec::new(), input, &[b"Hello"], BufferTooSmall, [12]);
+
+        assert_decode_buffer_sizes!(
+            LengthCodec::new(),
+            input,
+            CONFORMANCE_ITEMS,
+            BytesRemainingOnStream,
+            [32, 1024]
+        );
+
+        let error = assert_decode_splits_owned::<32, _>(
+            LengthCodecOwned::<16>::new(),
+            input,
+            &conformance_items(&[]),
+        )
+        .await;
+        assert!(matches!(error, FramedReadError::BytesRemainingOnStream));
     }
 
-    #[tokio::test]
-    async fn sink_stream() {
+    #[test]
+    fn hostile_input() {
+        let mut codec = LengthCodec::new();
+
+        assert_eq!(None, codec.decode_ref(b"\xFF\xFF\xFF\xFFHello").unwrap());
+    }
+
+    #[test]
+    fn conformance() {
         init_tracing();
 
-        let items: Vec<heapless::Vec<u8, 32>> = std::vec![
-            heapless::Vec::from_slice(b"Hello").unwrap(),
-            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
-            heapless::Vec::from_slice(b"").unwrap(),
-            heapless::Vec::from_slice(b"Hei").unwrap(),
-            heapless::Vec::from_slice(b"sup").unwrap(),
-            heapless::Vec::from_slice(b"Hey").unwrap(),
-        ];
+        assert_codec_conformance_items::<32, _>(LengthCodecOwned::<32>::new(), &[b""]);
 
-        let decoder = LengthCodecOwned::<32>::new();
-        let encoder = LengthCodecOwned::<32>::new();
+        let input = b"\x00\x00\x00\x05Hello\x00\x00\x00\x0DHello, world!\x00\x00\x00\x00\x00\x00\x00\x03Hey\x00\x00\x00\x0CHow are y";
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"", b"Hey"];
 
-        sink_stream!(encoder, decoder, items);
+        assert_decoder_conformance_truncated(LengthCodecOwned::<32>::new(), input, expected);
     }
 }
@@ -0,0 +1,710 @@
+//! Configurable length delimited codec for encoding and decoding bytes with a length field.
+
+use heapless::Vec;
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof};
+
+/// The width of a length field in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LengthFieldWidth {
+    /// A 1-byte length field.
+    One,
+    /// A 2-byte length field.
+    Two,
+    /// A 3-byte length field.
+    Three,
+    /// A 4-byte length field.
+    #[default]
+    Four,
+    /// An 8-byte length field.
+    Eight,
+}
+
+impl LengthFieldWidth {
+    /// Returns the width in bytes.
+    #[inline]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+            Self::Three => 3,
+            Self::Four => 4,
+            Self::Eight => 8,
+        }
+    }
+
+    /// Returns the largest length that fits into the length field.
+    #[inline]
+    pub const fn max_length(&self) -> u64 {
+        match self {
+            Self::Eight => u64::MAX,
+            _ => (1 << (self.size() * 8)) - 1,
+        }
+    }
+}
+
+/// The byte order of a length field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Endianness {
+    /// Most significant byte first.
+    #[default]
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+/// A codec that decodes frames delimited by a configurable length field and encodes a sequence of bytes into a sequence of bytes with a length field prefix.
+///
+/// A frame is made of a head of `length_field_offset` bytes followed by the length field, and a payload.
+/// The length of the payload is the value of the length field plus the `length_adjustment`.
+/// The decoded item is the frame without its first `num_skip` bytes, by default without its head.
+///
+/// The default codec is configured like [`LengthCodec`](crate::LengthCodec): a 4-byte big-endian length field counting only the payload.
+///
+/// Encoding writes the length field followed by the item, so only frames without a head before the length field can be encoded,
+/// with the item being the payload: encoding fails with [`LengthDelimitedEncodeError::UnsupportedFraming`]
+/// unless `length_field_offset` is `0` and `num_skip` is the default.
+///
+/// # Example
+///
+/// A 2-byte little-endian length field after a type byte, counting the whole frame:
+///
+/// ```rust
+/// use cody_c::{DecoderRef, Endianness, LengthDelimitedCodec, LengthFieldWidth};
+///
+/// let mut codec = LengthDelimitedCodec::builder()
+///     .length_field_offset(1)
+///     .length_field_width(LengthFieldWidth::Two)
+///     .endianness(Endianness::Little)
+///     .length_adjustment(-3)
+///     .num_skip(0)
+///     .build();
+///
+/// let (frame, size) = codec
+///     .decode_ref(b"\x07\x08\x00Hello\x07")
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(frame, b"\x07\x08\x00Hello");
+/// assert_eq!(size, 8);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LengthDelimitedCodec {
+    length_field_offset: usize,
+    length_field_width: LengthFieldWidth,
+    endianness: Endianness,
+    length_adjustment: isize,
+    num_skip: usize,
+    max_frame_length: usize,
+}
+
+impl Default for LengthDelimitedCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LengthDelimitedCodec {
+    /// Creates a new [`LengthDelimitedCodec`] with the default configuration.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Creates a new [`LengthDelimitedCodecBuilder`] with the default configuration.
+    #[inline]
+    pub const fn builder() -> LengthDelimitedCodecBuilder {
+        LengthDelimitedCodecBuilder::new()
+    }
+
+    /// Returns the number of bytes before the length field.
+    #[inline]
+    pub const fn length_field_offset(&self) -> usize {
+        self.length_field_offset
+    }
+
+    /// Returns the width of the length field.
+    #[inline]
+    pub const fn length_field_width(&self) -> LengthFieldWidth {
+        self.length_field_width
+    }
+
+    /// Returns the byte order of the length field.
+    #[inline]
+    pub const fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the value added to the length field to get the length of the payload.
+    #[inline]
+    pub const fn length_adjustment(&self) -> isize {
+        self.length_adjustment
+    }
+
+    /// Returns the number of bytes stripped from the beginning of a decoded frame.
+    #[inline]
+    pub const fn num_skip(&self) -> usize {
+        self.num_skip
+    }
+
+    /// Returns the maximum length of a payload.
+    #[inline]
+    pub const fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Returns the number of bytes before the payload.
+    #[inline]
+    const fn head_length(&self) -> usize {
+        self.length_field_offset + self.length_field_width.size()
+    }
+
+    /// Returns whether frames can be encoded: without a head before the length field and with the payload as item.
+    #[inline]
+    pub(crate) const fn is_encodable(&self) -> bool {
+        self.length_field_offset == 0 && self.num_skip == self.head_length()
+    }
+
+    /// Reads the length field at the beginning of `src`.
+    fn read_length(&self, src: &[u8]) -> u64 {
+        let src = &src[..self.length_field_width.size()];
+
+        let mut bytes = [0_u8; 8];
+
+        match self.endianness {
+            Endianness::Big => {
+                bytes[8 - src.len()..].copy_from_slice(src);
+
+                u64::from_be_bytes(bytes)
+            }
+            Endianness::Little => {
+                bytes[..src.len()].copy_from_slice(src);
+
+                u64::from_le_bytes(bytes)
+            }
+        }
+    }
+
+    /// Writes `length` into the beginning of `dst`.
+    fn write_length(&self, length: u64, dst: &mut [u8]) {
+        let width = self.length_field_width.size();
+
+        match self.endianness {
+            Endianness::Big => dst[..width].copy_from_slice(&length.to_be_bytes()[8 - width..]),
+            Endianness::Little => dst[..width].copy_from_slice(&length.to_le_bytes()[..width]),
+        }
+    }
+
+    /// Returns the length of the frame at the beginning of `src`, or `None` if the head is incomplete.
+    fn frame_length(&self, src: &[u8]) -> Result<Option<usize>, LengthDelimitedDecodeError> {
+        let head_length = self.head_length();
+
+        if src.len() < head_length {
+            return Ok(None);
+        }
+
+        let length = self.read_length(&src[self.length_field_offset..]) as i128
+            + self.length_adjustment as i128;
+
+        if length < 0 || length > usize::MAX as i128 {
+            return Err(LengthDelimitedDecodeError::InvalidLength);
+        }
+
+        let payload_length = length as usize;
+
+        if payload_length > self.max_frame_length {
+            return Err(LengthDelimitedDecodeError::FrameTooLarge);
+        }
+
+        let frame_length = head_length
+            .checked_add(payload_length)
+            .ok_or(LengthDelimitedDecodeError::InvalidLength)?;
+
+        if frame_length < self.num_skip {
+            return Err(LengthDelimitedDecodeError::InvalidLength);
+        }
+
+        Ok(Some(frame_length))
+    }
+}
+
+/// A builder for a [`LengthDelimitedCodec`].
+///
+/// The builder methods are `const`, so a codec can be configured in a `const` or `static`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LengthDelimitedCodecBuilder {
+    length_field_offset: usize,
+    length_field_width: LengthFieldWidth,
+    endianness: Endianness,
+    length_adjustment: isize,
+    num_skip: Option<usize>,
+    max_frame_length: usize,
+}
+
+impl Default for LengthDelimitedCodecBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LengthDelimitedCodecBuilder {
+    /// Creates a new [`LengthDelimitedCodecBuilder`] with the default configuration.
+    ///
+    /// - `length_field_offset`: `0`
+    /// - `length_field_width`: [`LengthFieldWidth::Four`]
+    /// - `endianness`: [`Endianness::Big`]
+    /// - `length_adjustment`: `0`
+    /// - `num_skip`: the number of bytes before the payload
+    /// - `max_frame_length`: `usize::MAX`
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            length_field_offset: 0,
+            length_field_width: LengthFieldWidth::Four,
+            endianness: Endianness::Big,
+            length_adjustment: 0,
+            num_skip: None,
+            max_frame_length: usize::MAX,
+        }
+    }
+
+    /// Sets the number of bytes before the length field.
+    #[inline]
+    pub const fn length_field_offset(mut self, length_field_offset: usize) -> Self {
+        self.length_field_offset = length_field_offset;
+        self
+    }
+
+    /// Sets the width of the length field.
+    #[inline]
+    pub const fn length_field_width(mut self, length_field_width: LengthFieldWidth) -> Self {
+        self.length_field_width = length_field_width;
+        self
+    }
+
+    /// Sets the byte order of the length field.
+    #[inline]
+    pub const fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Sets the value added to the length field to get the length of the payload.
+    ///
+    /// E.g. `-(offset + width)` for a length field counting the whole frame.
+    #[inline]
+    pub const fn length_adjustment(mut self, length_adjustment: isize) -> Self {
+        self.length_adjustment = length_adjustment;
+        self
+    }
+
+    /// Sets the number of bytes stripped from the beginning of a decoded frame.
+    #[inline]
+    pub const fn num_skip(mut self, num_skip: usize) -> Self {
+        self.num_skip = Some(num_skip);
+        self
+    }
+
+    /// Sets the maximum length of a payload.
+    ///
+    /// Decoding a larger payload fails with [`LengthDelimitedDecodeError::FrameTooLarge`] before it is buffered.
+    #[inline]
+    pub const fn max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    /// Builds the [`LengthDelimitedCodec`].
+    #[inline]
+    pub const fn build(self) -> LengthDelimitedCodec {
+        let head_length = self.length_field_offset + self.length_field_width.size();
+
+        LengthDelimitedCodec {
+            length_field_offset: self.length_field_offset,
+            length_field_width: self.length_field_width,
+            endianness: self.endianness,
+            length_adjustment: self.length_adjustment,
+            num_skip: match self.num_skip {
+                Some(num_skip) => num_skip,
+                None => head_length,
+            },
+            max_frame_length: self.max_frame_length,
+        }
+    }
+
+    /// Builds a [`LengthDelimitedCodecOwned`].
+    #[inline]
+    pub const fn build_owned<const N: usize>(self) -> LengthDelimitedCodecOwned<N> {
+        LengthDelimitedCodecOwned {
+            inner: self.build(),
+        }
+    }
+}
+
+/// An error that can occur when decoding a frame delimited by a length field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LengthDelimitedDecodeError {
+    /// The adjusted length is negative, overflows or is smaller than the number of bytes to skip.
+    InvalidLength,
+    /// The payload is longer than the maximum frame length.
+    FrameTooLarge,
+    /// The decoded frame does not fit into the item of a [`LengthDelimitedCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for LengthDelimitedDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "Invalid length"),
+            Self::FrameTooLarge => write!(f, "Frame too large"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthDelimitedDecodeError {}
+
+impl<'buf> Decoder<'buf> for LengthDelimitedCodec {
+    type Item = &'buf [u8];
+    type Error = LengthDelimitedDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for LengthDelimitedCodec {
+    type Item = &'buf [u8];
+    type Error = LengthDelimitedDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match self.frame_length(src)? {
+            Some(frame_length) if src.len() >= frame_length => {
+                let item = (&src[self.num_skip..frame_length], frame_length);
+
+                Ok(Some(item))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of bytes with a length field prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LengthDelimitedEncodeError {
+    /// The input buffer is too small to fit the encoded sequence of bytes.
+    BufferTooSmall,
+    /// The length of the item minus the length adjustment is negative or does not fit into the length field.
+    InvalidLength,
+    /// The item is longer than the maximum frame length.
+    FrameTooLarge,
+    /// The codec has a head before the length field or a `num_skip` other than the default, which can not be encoded.
+    UnsupportedFraming,
+}
+
+impl core::fmt::Display for LengthDelimitedEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::InvalidLength => write!(f, "Invalid length"),
+            Self::FrameTooLarge => write!(f, "Frame too large"),
+            Self::UnsupportedFraming => write!(f, "Unsupported framing"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthDelimitedEncodeError {}
+
+impl BufferTooSmallError for LengthDelimitedEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl Encoder<&[u8]> for LengthDelimitedCodec {
+    type Error = LengthDelimitedEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let width = self.encode_length(item.len(), dst)?;
+        let size = width + item.len();
+
+        dst[width..size].copy_from_slice(item);
+
+        Ok(size)
+    }
+}
+
+impl EncoderEof for LengthDelimitedCodec {
+    type Error = LengthDelimitedEncodeError;
+}
+
+impl LengthDelimitedCodec {
+    /// Writes the length field of a payload of `payload_length` bytes into the beginning of `dst`, returning the size of the length field.
+    ///
+    /// `dst` must be large enough to fit the payload after the length field.
+    pub(crate) fn encode_length(
+        &self,
+        payload_length: usize,
+        dst: &mut [u8],
+    ) -> Result<usize, LengthDelimitedEncodeError> {
+        if !self.is_encodable() {
+            return Err(LengthDelimitedEncodeError::UnsupportedFraming);
+        }
+
+        if payload_length > self.max_frame_length {
+            return Err(LengthDelimitedEncodeError::FrameTooLarge);
+        }
+
+        let length = payload_length as i128 - self.length_adjustment as i128;
+
+        if length < 0 || length > self.length_field_width.max_length() as i128 {
+            return Err(LengthDelimitedEncodeError::InvalidLength);
+        }
+
+        let width = self.length_field_width.size();
+
+        if dst.len() < width + payload_length {
+            return Err(LengthDelimitedEncodeError::BufferTooSmall);
+        }
+
+        self.write_length(length as u64, dst);
+
+        Ok(width)
+    }
+}
+
+/// An owned [`LengthDelimitedCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LengthDelimitedCodecOwned<const N: usize> {
+    inner: LengthDelimitedCodec,
+}
+
+impl<const N: usize> LengthDelimitedCodecOwned<N> {
+    /// Creates a new [`LengthDelimitedCodecOwned`] with the default configuration.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: LengthDelimitedCodec::new(),
+        }
+    }
+
+    /// Returns the configured [`LengthDelimitedCodec`].
+    #[inline]
+    pub const fn inner(&self) -> &LengthDelimitedCodec {
+        &self.inner
+    }
+}
+
+impl<const N: usize> From<LengthDelimitedCodec> for LengthDelimitedCodecOwned<N> {
+    fn from(inner: LengthDelimitedCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for LengthDelimitedCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = LengthDelimitedDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf, const N: usize> DecoderRef<'buf> for LengthDelimitedCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = LengthDelimitedDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src)? {
+            Some((bytes, size)) => {
+                let item =
+                    Vec::from_slice(bytes).map_err(|_| LengthDelimitedDecodeError::Capacity)?;
+
+                Ok(Some((item, size)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for LengthDelimitedCodecOwned<N> {
+    type Error = LengthDelimitedEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for LengthDelimitedCodecOwned<N> {
+    type Error = LengthDelimitedEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+    };
+
+    use super::*;
+
+    #[test]
+    fn default_is_length_codec() {
+        init_tracing();
+
+        let encoded =
+            assert_codec_conformance_items::<32, _>(LengthDelimitedCodecOwned::<32>::new(), &[b""]);
+
+        assert_eq!(
+            b"\x00\x00\x00\x05Hello\x00\x00\x00\x0DHello, world!\x00\x00\x00\x03Hei\x00\x00\x00\x03sup\x00\x00\x00\x03Hey\x00\x00\x00\x00",
+            encoded.as_slice()
+        );
+    }
+
+    #[test]
+    fn widths_and_endianness() {
+        init_tracing();
+
+        for width in [
+            LengthFieldWidth::One,
+            LengthFieldWidth::Two,
+            LengthFieldWidth::Three,
+            LengthFieldWidth::Four,
+            LengthFieldWidth::Eight,
+        ] {
+            for endianness in [Endianness::Big, Endianness::Little] {
+                let codec = LengthDelimitedCodec::builder()
+                    .length_field_width(width)
+                    .endianness(endianness)
+                    .build_owned::<32>();
+
+                let encoded = assert_codec_conformance_items::<32, _>(codec, &[b""]);
+
+                let length_field = &encoded[..width.size()];
+
+                match endianness {
+                    Endianness::Big => assert_eq!(5, length_field[width.size() - 1]),
+                    Endianness::Little => assert_eq!(5, length_field[0]),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn length_after_type_byte_counting_the_frame() {
+        init_tracing();
+
+        let codec = || {
+            LengthDelimitedCodec::builder()
+                .length_field_offset(1)
+                .length_field_width(LengthFieldWidth::Two)
+                .endianness(Endianness::Little)
+                .length_adjustment(-3)
+                .build_owned::<32>()
+        };
+
+        let input = b"\x01\x08\x00Hello\x02\x03\x00\x03\x10\x00Hello, world!\x04\x08\x00Hey";
+        let expected: &[&[u8]] = &[b"Hello", b"", b"Hello, world!"];
+
+        assert_decoder_conformance_truncated(codec(), input, expected);
+
+        // Keeps the head.
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_offset(1)
+            .length_field_width(LengthFieldWidth::Two)
+            .endianness(Endianness::Little)
+            .length_adjustment(-3)
+            .num_skip(0)
+            .build();
+
+        assert_eq!(
+            Some((&b"\x01\x08\x00Hello"[..], 8)),
+            codec.decode_ref(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn encode_unsupported_framing() {
+        init_tracing();
+
+        let mut dst = [0_u8; 16];
+
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_offset(1)
+            .build();
+
+        assert_eq!(
+            Err(LengthDelimitedEncodeError::UnsupportedFraming),
+            codec.encode(b"Hello", &mut dst)
+        );
+
+        let mut codec = LengthDelimitedCodec::builder().num_skip(0).build();
+
+        assert_eq!(
+            Err(LengthDelimitedEncodeError::UnsupportedFraming),
+            codec.encode(b"Hello", &mut dst)
+        );
+
+        // Setting the default explicitly is supported.
+        let mut codec = LengthDelimitedCodec::builder().num_skip(4).build();
+
+        assert_eq!(Ok(9), codec.encode(b"Hello", &mut dst));
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        init_tracing();
+
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_width(LengthFieldWidth::One)
+            .max_frame_length(4)
+            .build();
+
+        assert_eq!(
+            Err(LengthDelimitedDecodeError::FrameTooLarge),
+            codec.decode_ref(b"\x05Hello")
+        );
+
+        assert_eq!(
+            Err(LengthDelimitedEncodeError::FrameTooLarge),
+            codec.encode(b"Hello", &mut [0_u8; 8])
+        );
+
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_width(LengthFieldWidth::One)
+            .length_adjustment(-2)
+            .build();
+
+        assert_eq!(
+            Err(LengthDelimitedDecodeError::InvalidLength),
+            codec.decode_ref(b"\x01Hello")
+        );
+
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_width(LengthFieldWidth::One)
+            .build();
+
+        assert_eq!(
+            Err(LengthDelimitedEncodeError::InvalidLength),
+            codec.encode(&[0_u8; 256], &mut [0_u8; 512])
+        );
+
+        let mut codec = LengthDelimitedCodecOwned::<4>::new();
+
+        assert_eq!(
+            Err(LengthDelimitedDecodeError::Capacity),
+            codec.decode_ref(b"\x00\x00\x00\x05Hello")
+        );
+    }
+}
@@ -5,8 +5,8 @@ use core::convert::Infallible;
 use heapless::Vec;
 
 use crate::{
-    decode::{Decoder, DecoderOwned},
-    encode::Encoder,
+    decode::{Decoder, DecoderOwned, DecoderRef},
+    encode::{BufferTooSmallError, Encoder, EncoderEof},
 };
 
 /// A codec that decodes a sequence of bytes into a line and encodes a line into a sequence of bytes.
@@ -42,6 +42,15 @@ impl<'buf> Decoder<'buf> for LinesCodec {
     type Error = Infallible;
 
     fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for LinesCodec {
+    type Item = &'buf [u8];
+    type Error = Infallible;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
         while self.seen < src.len() {
             if src[self.seen] == b'\n' {
                 let line_bytes = match &src[..self.seen].last() {
@@ -82,6 +91,12 @@ impl core::fmt::Display for LinesEncodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for LinesEncodeError {}
 
+impl BufferTooSmallError for LinesEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
 impl Encoder<&[u8]> for LinesCodec {
     type Error = LinesEncodeError;
 
@@ -99,6 +114,10 @@ impl Encoder<&[u8]> for LinesCodec {
     }
 }
 
+impl EncoderEof for LinesCodec {
+    type Error = LinesEncodeError;
+}
+
 /// An owned [`LinesCodec`].
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -150,6 +169,22 @@ impl<const N: usize> DecoderOwned for LinesCodecOwned<N> {
     }
 }
 
+impl<'buf, const N: usize> DecoderRef<'buf> for LinesCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = ();
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src) {
+            Ok(Some((bytes, size))) => {
+                let item = Vec::from_slice(bytes)?;
+                Ok(Some((item, size)))
+            }
+            Ok(None) => Ok(None),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 impl<const N: usize> Encoder<Vec<u8, N>> for LinesCodecOwned<N> {
     type Error = LinesEncodeError;
 
@@ -158,18 +193,21 @@ impl<const N: usize> Encoder<Vec<u8, N>> for LinesCodecOwned<N> {
     }
 }
 
+impl<const N: usize> EncoderEof for LinesCodecOwned<N> {
+    type Error = LinesEncodeError;
+}
+
 #[cfg(test)]
 mod test {
     extern crate std;
 
-    use std::vec::Vec;
-
-    use futures::{pin_mut, SinkExt, StreamExt};
-    use tokio::io::AsyncWriteExt;
-
     use crate::{
-        error, framed_read, sink_stream, test::init_tracing, tokio::Compat, FramedRead,
-        FramedReadError, FramedWrite,
+        test::init_tracing,
+        testing::{
+            assert_codec_conformance_items, assert_decode_buffer_sizes, assert_decode_splits,
+            assert_decoder_conformance_truncated, to_vecs, CONFORMANCE_ITEMS,
+        },
+        FramedReadError,
     };
 
     use super::*;
@@ -178,66 +216,38 @@ mod test {
     async fn framed_read() {
         init_tracing();
 
-        let items: &[&[u8]] = &[
-            b"Hel",
-            b"lo\n",
-            b"Hell",
-            b"o, world!\n",
-            b"H",
-            b"ei\r\n",
-            b"sup",
-            b"\n",
-            b"Hey\r",
-            b"\n",
-            b"How ",
-            b"are y",
-        ];
-
-        let decoder = LinesCodec::new();
-
-        let expected: &[&[u8]] = &[];
-        framed_read!(items, expected, decoder, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 1, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 2, 4, BufferTooSmall);
-
-        framed_read!(items, expected, decoder, 4, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 1, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 2, BufferTooSmall);
-        framed_read!(items, expected, decoder, 4, 4, BufferTooSmall);
-
-        let expected: &[&[u8]] = &[b"Hello"];
-        framed_read!(items, expected, decoder, 8, BufferTooSmall);
+        let input = b"Hello\nHello, world!\nHei\r\nsup\nHey\r\nHow are y";
 
-        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
-        framed_read!(items, expected, decoder, 16, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 16, 1, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 16, 2, BytesRemainingOnStream);
-        framed_read!(items, expected, decoder, 16, 4, BytesRemainingOnStream);
+        assert_decode_buffer_sizes!(LinesCodec::new(), input, &[], BufferTooSmall, [1, 2, 4]);
 
-        framed_read!(items, expected, decoder);
+        assert_decode_buffer_sizes!(LinesCodec::new(), input, &[b"Hello"], BufferTooSmall, [8]);
+
+        assert_decode_buffer_sizes!(
+            LinesCodec::new(),
+            input,
+            CONFORMANCE_ITEMS,
+            BytesRemainingOnStream,
+            [16, 32, 1024]
+        );
+
+        let error = assert_decode_splits::<16, _, _, _>(
+            LinesCodec::new(),
+            input,
+            &to_vecs(CONFORMANCE_ITEMS),
+        )
+        .await;
+        assert!(matches!(error, FramedReadError::BytesRemainingOnStream));
     }
 
-    #[tokio::test]
-    async fn sink_stream() {
+    #[test]
+    fn conformance() {
         init_tracing();
 
-        let items: Vec<heapless::Vec<u8, 32>> = std::vec![
-            heapless::Vec::from_slice(b"Hello").unwrap(),
-            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
-            heapless::Vec::from_slice(b"Hei").unwrap(),
-            heapless::Vec::from_slice(b"sup").unwrap(),
-            heapless::Vec::from_slice(b"Hey").unwrap(),
-        ];
+        assert_codec_conformance_items::<32, _>(LinesCodecOwned::<32>::new(), &[]);
 
-        let decoder = LinesCodecOwned::<32>::new();
-        let encoder = LinesCodecOwned::<32>::new();
+        let input = b"Hello\nHello, world!\nHei\r\nsup\nHey\r\nHow are y";
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
 
-        sink_stream!(encoder, decoder, items);
+        assert_decoder_conformance_truncated(LinesCodecOwned::<32>::new(), input, expected);
     }
 }
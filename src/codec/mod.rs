@@ -2,10 +2,20 @@
 
 pub mod any;
 pub mod bytes;
+pub mod checksummed;
+pub mod cobs;
+pub mod hdlc;
 pub mod length;
+pub mod length_delimited;
 pub mod lines;
+pub mod slip;
+pub mod stx_etx;
+pub mod varint;
 
-pub use self::{any::*, bytes::*, length::*, lines::*};
+pub use self::{
+    any::*, bytes::*, checksummed::*, cobs::*, hdlc::*, length::*, length_delimited::*, lines::*,
+    slip::*, stx_etx::*, varint::*,
+};
 
 #[cfg(feature = "bincode")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bincode")))]
@@ -13,3 +23,17 @@ pub mod bincode;
 
 #[cfg(feature = "bincode")]
 pub use self::bincode::*;
+
+#[cfg(feature = "postcard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "postcard")))]
+pub mod postcard;
+
+#[cfg(feature = "postcard")]
+pub use self::postcard::*;
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub mod ndjson;
+
+#[cfg(feature = "json")]
+pub use self::ndjson::*;
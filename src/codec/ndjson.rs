@@ -0,0 +1,460 @@
+//! Newline delimited JSON codec for encoding and decoding serde data structures.
+
+use core::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof};
+
+/// A codec that decodes a line of JSON into a serde data structure and encodes a serde data structure into a line of JSON.
+///
+/// Lines are delimited by `\n`, an optional trailing `\r` is ignored and blank lines are skipped.
+/// `&str` fields are borrowed from the buffer, strings containing escape sequences are unescaped into a scratch area of `N` bytes
+/// and can only be decoded into owned fields, e.g. [`heapless::String`].
+///
+/// A line that fails to decode is skipped by the next call with the same buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{DecoderRef, Encoder, NdJsonCodec};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Message<'a> {
+///     id: u32,
+///     name: &'a str,
+/// }
+///
+/// let mut codec = NdJsonCodec::<Message, 32>::new();
+/// let mut dst = [0_u8; 32];
+///
+/// let size = codec.encode(Message { id: 1, name: "Hello" }, &mut dst).unwrap();
+///
+/// assert_eq!(&dst[..size], b"{\"id\":1,\"name\":\"Hello\"}\n");
+/// assert_eq!(
+///     codec.decode_ref(&dst[..size]).unwrap(),
+///     Some((Message { id: 1, name: "Hello" }, size))
+/// );
+/// ```
+pub struct NdJsonCodec<T, const N: usize> {
+    /// The start of the current line in the slice.
+    start: usize,
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+    /// The number of lines seen so far.
+    line: usize,
+    /// The offset of the slice in the stream.
+    position: usize,
+    /// The buffer used to unescape strings.
+    scratch: [u8; N],
+    _de: PhantomData<T>,
+}
+
+impl<T, const N: usize> NdJsonCodec<T, N> {
+    /// Creates a new [`NdJsonCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            start: 0,
+            seen: 0,
+            line: 0,
+            position: 0,
+            scratch: [0; N],
+            _de: PhantomData,
+        }
+    }
+
+    /// Returns the number of lines seen so far.
+    #[inline]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the offset of the next line in the stream.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position + self.start
+    }
+
+    /// Clears the state of the codec to start decoding a new stream.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.skip(0);
+        self.line = 0;
+        self.position = 0;
+    }
+
+    /// Skips the first `size` bytes of the next slice.
+    #[inline]
+    fn skip(&mut self, size: usize) {
+        self.start = size;
+        self.seen = size;
+    }
+}
+
+impl<T, const N: usize> Default for NdJsonCodec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Clone for NdJsonCodec<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            start: self.start,
+            seen: self.seen,
+            line: self.line,
+            position: self.position,
+            scratch: self.scratch,
+            _de: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for NdJsonCodec<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NdJsonCodec")
+            .field("start", &self.start)
+            .field("seen", &self.seen)
+            .field("line", &self.line)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T, const N: usize> defmt::Format for NdJsonCodec<T, N> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "NdJsonCodec {{ start: {}, seen: {}, line: {}, position: {} }}",
+            self.start,
+            self.seen,
+            self.line,
+            self.position
+        )
+    }
+}
+
+/// An error that can occur when decoding a line of JSON into a serde data structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NdJsonDecodeError {
+    /// The line is not a valid JSON representation of the item.
+    Deserialize {
+        /// The number of the line, starting at `1`.
+        line: usize,
+        /// The offset of the line in the stream.
+        offset: usize,
+        /// The JSON error.
+        error: serde_json_core::de::Error,
+    },
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for NdJsonDecodeError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Deserialize { line, offset, .. } => {
+                defmt::write!(f, "Deserialize error at line {} (offset {})", line, offset)
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for NdJsonDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Deserialize {
+                line,
+                offset,
+                error,
+            } => write!(
+                f,
+                "Deserialize error at line {} (offset {}): {}",
+                line, offset, error
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NdJsonDecodeError {}
+
+impl<'buf, T, const N: usize> Decoder<'buf> for NdJsonCodec<T, N>
+where
+    T: Deserialize<'buf>,
+{
+    type Item = T;
+    type Error = NdJsonDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf, T, const N: usize> DecoderRef<'buf> for NdJsonCodec<T, N>
+where
+    T: Deserialize<'buf>,
+{
+    type Item = T;
+    type Error = NdJsonDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        while self.seen < src.len() {
+            if src[self.seen] != b'\n' {
+                self.seen += 1;
+
+                continue;
+            }
+
+            self.line += 1;
+
+            let size = self.seen + 1;
+            let line_bytes = match &src[self.start..self.seen] {
+                [line_bytes @ .., b'\r'] => line_bytes,
+                line_bytes => line_bytes,
+            };
+
+            if line_bytes.iter().all(u8::is_ascii_whitespace) {
+                self.skip(size);
+
+                continue;
+            }
+
+            let offset = self.position();
+
+            // The line is consumed with the next one if it fails to decode.
+            self.skip(size);
+
+            let (item, _) = serde_json_core::from_slice_escaped(line_bytes, &mut self.scratch)
+                .map_err(|error| NdJsonDecodeError::Deserialize {
+                    line: self.line,
+                    offset,
+                    error,
+                })?;
+
+            self.skip(0);
+            self.position += size;
+
+            return Ok(Some((item, size)));
+        }
+
+        Ok(None)
+    }
+}
+
+impl<T, const N: usize> DecoderOwned for NdJsonCodec<T, N>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = NdJsonDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+/// An error that can occur when encoding a serde data structure into a line of JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NdJsonEncodeError {
+    /// The input buffer is too small to fit the encoded line.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for NdJsonEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NdJsonEncodeError {}
+
+impl BufferTooSmallError for NdJsonEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl<T, const N: usize> Encoder<T> for NdJsonCodec<T, N>
+where
+    T: Serialize,
+{
+    type Error = NdJsonEncodeError;
+
+    fn encode(&mut self, item: T, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let len =
+            serde_json_core::to_slice(&item, dst).map_err(|_| NdJsonEncodeError::BufferTooSmall)?;
+
+        match dst.get_mut(len) {
+            Some(byte) => *byte = b'\n',
+            None => return Err(NdJsonEncodeError::BufferTooSmall),
+        }
+
+        Ok(len + 1)
+    }
+}
+
+impl<T, const N: usize> EncoderEof for NdJsonCodec<T, N> {
+    type Error = NdJsonEncodeError;
+}
+
+#[cfg(all(test, feature = "std", feature = "tokio"))]
+mod test {
+    extern crate std;
+
+    use core::str::FromStr;
+    use std::vec::Vec;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance, assert_roundtrip},
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum JsonMessage {
+        Numbers { a: u32, b: u32, c: u32 },
+        String(heapless::String<32>),
+        Vec(heapless::Vec<u8, 32>),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct BorrowedMessage<'a> {
+        id: u16,
+        name: &'a str,
+    }
+
+    fn test_messages() -> Vec<JsonMessage> {
+        std::vec![
+            JsonMessage::Numbers { a: 1, b: 2, c: 3 },
+            JsonMessage::String(heapless::String::from_str("Hello").unwrap()),
+            JsonMessage::String(heapless::String::from_str("Hello,\n\"world\"!\r\n").unwrap()),
+            JsonMessage::Vec(heapless::Vec::from_slice(b"Hello, world!").unwrap()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn roundtrip() {
+        init_tracing();
+
+        assert_roundtrip::<1024, _, _, _>(
+            NdJsonCodec::<JsonMessage, 32>::new(),
+            NdJsonCodec::<JsonMessage, 32>::new(),
+            &test_messages(),
+        )
+        .await;
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        let encoded = assert_codec_conformance::<1024, _, _, _>(
+            NdJsonCodec::<JsonMessage, 32>::new(),
+            NdJsonCodec::<JsonMessage, 32>::new(),
+            &test_messages(),
+        );
+
+        assert_eq!(
+            test_messages().len(),
+            encoded.iter().filter(|byte| **byte == b'\n').count()
+        );
+    }
+
+    #[test]
+    fn borrowed() {
+        init_tracing();
+
+        let mut codec = NdJsonCodec::<BorrowedMessage, 0>::new();
+        let src = b"\r\n{\"id\":1,\"name\":\"Hello\"}\r\n{\"id\":2,\"name\":\"a\\\"b\"}\n";
+
+        assert_eq!(
+            Ok(Some((
+                BorrowedMessage {
+                    id: 1,
+                    name: "Hello"
+                },
+                27
+            ))),
+            codec.decode_ref(src)
+        );
+
+        assert!(matches!(
+            codec.decode_ref(&src[27..]),
+            Err(NdJsonDecodeError::Deserialize {
+                line: 3,
+                offset: 27,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        init_tracing();
+
+        let mut codec = NdJsonCodec::<JsonMessage, 8>::new();
+        let src = b"{\"Numbers\":{\"a\":1,\"b\":2,\"c\":3}}\n\n{\"Numbers\":{\"a\":1,\"b\":2}}\n{\"String\":\"\\n\\n\\n\\n\\n\\n\\n\\n\\n\"}\n{\"String\":\"ok\"}\n";
+
+        assert_eq!(
+            Ok(Some((JsonMessage::Numbers { a: 1, b: 2, c: 3 }, 32))),
+            codec.decode_ref(src)
+        );
+
+        let src = &src[32..];
+
+        assert_eq!(
+            Err(NdJsonDecodeError::Deserialize {
+                line: 3,
+                offset: 33,
+                error: serde_json_core::de::Error::CustomError
+            }),
+            codec.decode_ref(src)
+        );
+        assert_eq!(
+            Err(NdJsonDecodeError::Deserialize {
+                line: 4,
+                offset: 59,
+                error: serde_json_core::de::Error::EscapedStringIsTooLong
+            }),
+            codec.decode_ref(src)
+        );
+        assert_eq!(
+            Ok(Some((
+                JsonMessage::String(heapless::String::from_str("ok").unwrap()),
+                src.len()
+            ))),
+            codec.decode_ref(src)
+        );
+        assert_eq!(src.len() + 32, codec.position());
+    }
+
+    #[test]
+    fn buffer_too_small() {
+        init_tracing();
+
+        let mut codec = NdJsonCodec::<JsonMessage, 0>::new();
+
+        assert_eq!(
+            Err(NdJsonEncodeError::BufferTooSmall),
+            codec.encode(JsonMessage::Numbers { a: 1, b: 2, c: 3 }, &mut [0_u8; 8])
+        );
+        assert_eq!(
+            Err(NdJsonEncodeError::BufferTooSmall),
+            codec.encode(JsonMessage::Numbers { a: 1, b: 2, c: 3 }, &mut [0_u8; 31])
+        );
+        assert_eq!(
+            Ok(32),
+            codec.encode(JsonMessage::Numbers { a: 1, b: 2, c: 3 }, &mut [0_u8; 32])
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! Postcard codec for encoding and decoding serde data structures framed with COBS or a payload length prefix.
+
+use core::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    BufferTooSmallError, CobsCodec, CobsDecodeError, Decoder, DecoderOwned, DecoderRef, Encoder,
+    EncoderEof, LengthDelimitedCodec, LengthDelimitedDecodeError, LengthDelimitedEncodeError,
+};
+
+/// The framing of the postcard payloads of a [`PostcardCodec`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PostcardFraming {
+    /// Payloads are COBS encoded and terminated by a `0x00` delimiter, see [`CobsCodec`].
+    Cobs(CobsCodec),
+    /// Payloads are prefixed with their length, see [`LengthDelimitedCodec`].
+    LengthDelimited(LengthDelimitedCodec),
+}
+
+impl Default for PostcardFraming {
+    fn default() -> Self {
+        Self::Cobs(CobsCodec::new())
+    }
+}
+
+/// A codec that decodes a sequence of framed bytes into a serde data structure and encodes a serde data structure into a sequence of framed bytes, using the postcard wire format.
+///
+/// Items borrowing from the buffer, e.g. `&str` or `&[u8]` fields, are decoded without copying through [`Decoder`].
+/// Owned items are decoded through [`DecoderOwned`].
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{Decoder, Encoder, LengthDelimitedCodec, PostcardCodec};
+///
+/// let mut codec = PostcardCodec::<&str>::new_length_delimited(LengthDelimitedCodec::new());
+/// let mut dst = [0_u8; 32];
+///
+/// let size = codec.encode("Hello", &mut dst).unwrap();
+///
+/// assert_eq!(&dst[..size], b"\x00\x00\x00\x06\x05Hello");
+/// assert_eq!(codec.decode(&mut dst[..size]).unwrap(), Some(("Hello", size)));
+/// ```
+pub struct PostcardCodec<T> {
+    framing: PostcardFraming,
+    _de: PhantomData<T>,
+}
+
+impl<T> PostcardCodec<T> {
+    /// Creates a new [`PostcardCodec`] with COBS framing.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::new_with_framing(PostcardFraming::Cobs(CobsCodec::new()))
+    }
+
+    /// Creates a new [`PostcardCodec`] with a payload length prefix described by `length_codec`.
+    ///
+    /// The frame is the length field followed by the payload, so the `length_codec` must have a `length_field_offset` of `0` and the default `num_skip`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `length_codec` has a head before the length field or a `num_skip` other than the default.
+    #[inline]
+    pub const fn new_length_delimited(length_codec: LengthDelimitedCodec) -> Self {
+        Self::new_with_framing(PostcardFraming::LengthDelimited(length_codec))
+    }
+
+    /// Creates a new [`PostcardCodec`] with the given `framing`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `framing` is [`PostcardFraming::LengthDelimited`] with an unsupported length codec, see [`PostcardCodec::new_length_delimited`].
+    #[inline]
+    pub const fn new_with_framing(framing: PostcardFraming) -> Self {
+        if let PostcardFraming::LengthDelimited(length_codec) = &framing {
+            assert!(
+                length_codec.is_encodable(),
+                "The length codec must have a length field offset of 0 and the default num_skip"
+            );
+        }
+
+        Self {
+            framing,
+            _de: PhantomData,
+        }
+    }
+
+    /// Returns the framing of the postcard payloads.
+    #[inline]
+    pub const fn framing(&self) -> &PostcardFraming {
+        &self.framing
+    }
+
+    fn decode_frame<'buf>(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(&'buf [u8], usize)>, PostcardDecodeError> {
+        match &mut self.framing {
+            PostcardFraming::Cobs(codec) => codec.decode(src).map_err(PostcardDecodeError::Cobs),
+            PostcardFraming::LengthDelimited(codec) => {
+                codec.decode_ref(src).map_err(PostcardDecodeError::Length)
+            }
+        }
+    }
+}
+
+impl<T> Default for PostcardCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PostcardCodec<T> {
+    fn clone(&self) -> Self {
+        Self::new_with_framing(self.framing.clone())
+    }
+}
+
+impl<T> core::fmt::Debug for PostcardCodec<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PostcardCodec")
+            .field("framing", &self.framing)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for PostcardCodec<T> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PostcardCodec {{ framing: {} }}", self.framing)
+    }
+}
+
+/// An error that can occur when decoding a sequence of framed bytes into a serde data structure.
+#[derive(Debug)]
+pub enum PostcardDecodeError {
+    /// The COBS framing is invalid.
+    Cobs(CobsDecodeError),
+    /// The payload length prefix is invalid.
+    Length(LengthDelimitedDecodeError),
+    /// A postcard error occurred.
+    Deserialize(postcard::Error),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PostcardDecodeError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Cobs(err) => defmt::write!(f, "COBS error: {}", err),
+            Self::Length(err) => defmt::write!(f, "Length error: {}", err),
+            Self::Deserialize(_) => defmt::write!(f, "Deserialize error"),
+        }
+    }
+}
+
+impl core::fmt::Display for PostcardDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Cobs(err) => write!(f, "COBS error: {}", err),
+            Self::Length(err) => write!(f, "Length error: {}", err),
+            Self::Deserialize(err) => write!(f, "Deserialize error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PostcardDecodeError {}
+
+impl<'buf, T> Decoder<'buf> for PostcardCodec<T>
+where
+    T: Deserialize<'buf>,
+{
+    type Item = T;
+    type Error = PostcardDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match self.decode_frame(src)? {
+            None => Ok(None),
+            Some((bytes, size)) => {
+                let de = postcard::from_bytes(bytes).map_err(PostcardDecodeError::Deserialize)?;
+
+                Ok(Some((de, size)))
+            }
+        }
+    }
+}
+
+impl<T> DecoderOwned for PostcardCodec<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = PostcardDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode(src)
+    }
+}
+
+/// An error that can occur when encoding a serde data structure into a sequence of framed bytes.
+#[derive(Debug)]
+pub enum PostcardEncodeError {
+    /// The input buffer is too small to fit the encoded item.
+    BufferTooSmall,
+    /// A postcard error occurred.
+    Serialize(postcard::Error),
+    /// The payload does not fit into the payload length prefix.
+    Length(LengthDelimitedEncodeError),
+}
+
+impl From<postcard::Error> for PostcardEncodeError {
+    fn from(err: postcard::Error) -> Self {
+        match err {
+            postcard::Error::SerializeBufferFull => Self::BufferTooSmall,
+            err => Self::Serialize(err),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PostcardEncodeError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::BufferTooSmall => defmt::write!(f, "Buffer too small"),
+            Self::Serialize(_) => defmt::write!(f, "Serialize error"),
+            Self::Length(err) => defmt::write!(f, "Length error: {}", err),
+        }
+    }
+}
+
+impl core::fmt::Display for PostcardEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::Serialize(err) => write!(f, "Serialize error: {}", err),
+            Self::Length(err) => write!(f, "Length error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PostcardEncodeError {}
+
+impl BufferTooSmallError for PostcardEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        match self {
+            Self::BufferTooSmall | Self::Serialize(postcard::Error::SerializeBufferFull) => true,
+            Self::Length(err) => err.is_buffer_too_small(),
+            Self::Serialize(_) => false,
+        }
+    }
+}
+
+impl<T> Encoder<T> for PostcardCodec<T>
+where
+    T: Serialize,
+{
+    type Error = PostcardEncodeError;
+
+    fn encode(&mut self, item: T, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        match &self.framing {
+            PostcardFraming::Cobs(_) => Ok(postcard::to_slice_cobs(&item, dst)?.len()),
+            PostcardFraming::LengthDelimited(codec) => {
+                let prefix_len = codec.length_field_width().size();
+
+                if dst.len() < prefix_len {
+                    return Err(PostcardEncodeError::BufferTooSmall);
+                }
+
+                let payload_len = postcard::to_slice(&item, &mut dst[prefix_len..])?.len();
+
+                codec
+                    .encode_length(payload_len, dst)
+                    .map_err(PostcardEncodeError::Length)?;
+
+                Ok(payload_len + prefix_len)
+            }
+        }
+    }
+}
+
+impl<T> EncoderEof for PostcardCodec<T> {
+    type Error = PostcardEncodeError;
+}
+
+#[cfg(all(test, feature = "std", feature = "tokio"))]
+mod test {
+    extern crate std;
+
+    use core::str::FromStr;
+    use std::vec::Vec;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance, assert_roundtrip},
+        LengthFieldWidth,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum PostcardMessage {
+        Numbers(u32, u32, u32),
+        String(heapless::String<32>),
+        Vec(heapless::Vec<u8, 32>),
+        Zeros([u8; 8]),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct BorrowedMessage<'a> {
+        id: u16,
+        name: &'a str,
+        bytes: &'a [u8],
+    }
+
+    fn test_messages() -> Vec<PostcardMessage> {
+        std::vec![
+            PostcardMessage::Numbers(1, 2, 3),
+            PostcardMessage::String(heapless::String::from_str("Hello").unwrap()),
+            PostcardMessage::Vec(heapless::Vec::from_slice(b"Hello, world!").unwrap()),
+            PostcardMessage::Zeros([0; 8]),
+        ]
+    }
+
+    fn length_codec() -> LengthDelimitedCodec {
+        LengthDelimitedCodec::builder()
+            .length_field_width(LengthFieldWidth::Two)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn roundtrip() {
+        init_tracing();
+
+        assert_roundtrip::<1024, _, _, _>(
+            PostcardCodec::<PostcardMessage>::new(),
+            PostcardCodec::<PostcardMessage>::new(),
+            &test_messages(),
+        )
+        .await;
+
+        assert_roundtrip::<1024, _, _, _>(
+            PostcardCodec::<PostcardMessage>::new_length_delimited(length_codec()),
+            PostcardCodec::<PostcardMessage>::new_length_delimited(length_codec()),
+            &test_messages(),
+        )
+        .await;
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        let encoded = assert_codec_conformance::<1024, _, _, _>(
+            PostcardCodec::<PostcardMessage>::new(),
+            PostcardCodec::<PostcardMessage>::new(),
+            &test_messages(),
+        );
+
+        assert_eq!(
+            test_messages().len(),
+            encoded.iter().filter(|byte| **byte == 0).count()
+        );
+
+        assert_codec_conformance::<1024, _, _, _>(
+            PostcardCodec::<PostcardMessage>::new_length_delimited(length_codec()),
+            PostcardCodec::<PostcardMessage>::new_length_delimited(length_codec()),
+            &test_messages(),
+        );
+    }
+
+    #[test]
+    fn borrowed() {
+        init_tracing();
+
+        let message = BorrowedMessage {
+            id: 7,
+            name: "Hello",
+            bytes: b"\x00world\x00",
+        };
+
+        for mut codec in [
+            PostcardCodec::<BorrowedMessage>::new(),
+            PostcardCodec::<BorrowedMessage>::new_length_delimited(length_codec()),
+        ] {
+            let mut dst = [0_u8; 64];
+
+            let size = codec.encode(message, &mut dst).expect("Must encode");
+
+            let (decoded, consumed) = codec
+                .decode(&mut dst[..size])
+                .expect("Must decode")
+                .expect("Must be a frame");
+
+            assert_eq!(message, decoded);
+            assert_eq!(size, consumed);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        init_tracing();
+
+        let mut codec = PostcardCodec::<PostcardMessage>::new();
+
+        assert!(matches!(
+            codec.encode(PostcardMessage::Numbers(1, 2, 3), &mut [0_u8; 2]),
+            Err(PostcardEncodeError::BufferTooSmall)
+        ));
+
+        assert!(matches!(
+            codec.decode_owned(&mut { *b"\x02\x09\x00" }),
+            Err(PostcardDecodeError::Deserialize(_))
+        ));
+
+        let mut codec = PostcardCodec::<PostcardMessage>::new_length_delimited(length_codec());
+
+        assert!(matches!(
+            codec.encode(PostcardMessage::Numbers(1, 2, 3), &mut [0_u8; 1]),
+            Err(PostcardEncodeError::BufferTooSmall)
+        ));
+
+        let mut codec = PostcardCodec::<PostcardMessage>::new_length_delimited(
+            LengthDelimitedCodec::builder().max_frame_length(4).build(),
+        );
+
+        assert!(matches!(
+            codec.decode_owned(&mut { *b"\x00\x00\x00\x05\x01Hello" }),
+            Err(PostcardDecodeError::Length(
+                LengthDelimitedDecodeError::FrameTooLarge
+            ))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "length field offset of 0")]
+    fn length_codec_with_head() {
+        let _ = PostcardCodec::<PostcardMessage>::new_length_delimited(
+            LengthDelimitedCodec::builder()
+                .length_field_offset(1)
+                .build(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "default num_skip")]
+    fn length_codec_keeping_the_length_field() {
+        let _ = PostcardCodec::<PostcardMessage>::new_with_framing(
+            PostcardFraming::LengthDelimited(LengthDelimitedCodec::builder().num_skip(0).build()),
+        );
+    }
+}
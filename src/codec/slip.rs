@@ -0,0 +1,546 @@
+//! SLIP (RFC 1055) codec for encoding and decoding bytes delimited by an END byte, with an optional KISS mode.
+
+use heapless::Vec;
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, Encoder, EncoderEof};
+
+/// The byte that delimits SLIP frames.
+pub const SLIP_END: u8 = 0xC0;
+
+/// The byte that starts a SLIP escape sequence.
+pub const SLIP_ESC: u8 = 0xDB;
+
+/// The byte that follows [`SLIP_ESC`] to encode a [`SLIP_END`] byte.
+pub const SLIP_ESC_END: u8 = 0xDC;
+
+/// The byte that follows [`SLIP_ESC`] to encode a [`SLIP_ESC`] byte.
+pub const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// The port and command of a KISS frame, carried in the first byte of the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KissHeader {
+    byte: u8,
+}
+
+impl KissHeader {
+    /// Creates a new [`KissHeader`] with the given `port` and `command`.
+    ///
+    /// Only the lower four bits of `port` and `command` are used.
+    #[inline]
+    pub const fn new(port: u8, command: u8) -> Self {
+        Self {
+            byte: (port & 0x0F) << 4 | (command & 0x0F),
+        }
+    }
+
+    /// Creates a new [`KissHeader`] from the first byte of a KISS frame.
+    #[inline]
+    pub const fn from_byte(byte: u8) -> Self {
+        Self { byte }
+    }
+
+    /// Returns the port.
+    #[inline]
+    pub const fn port(&self) -> u8 {
+        self.byte >> 4
+    }
+
+    /// Returns the command.
+    #[inline]
+    pub const fn command(&self) -> u8 {
+        self.byte & 0x0F
+    }
+
+    /// Returns the first byte of a KISS frame.
+    #[inline]
+    pub const fn byte(&self) -> u8 {
+        self.byte
+    }
+}
+
+/// The framing mode of a [`SlipCodec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlipMode {
+    /// Plain SLIP frames.
+    #[default]
+    Slip,
+    /// KISS frames. Encoded frames start with the given header, decoded frames have their header stripped.
+    Kiss(KissHeader),
+}
+
+/// A codec that decodes a sequence of SLIP encoded bytes ending with an END byte into a sequence of bytes and encodes a sequence of bytes into a sequence of SLIP encoded bytes surrounded by END bytes.
+///
+/// Decoding happens in place inside the buffer given to [`Decoder::decode`], the returned item borrows the unescaped bytes.
+/// Leading END bytes and empty frames are skipped, they are consumed together with the next frame.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{Decoder, KissHeader, SlipCodec};
+///
+/// let mut codec = SlipCodec::new();
+/// let mut src = *b"\xC0\xC0ab\xDB\xDCc\xC0";
+///
+/// assert_eq!(
+///     codec.decode(&mut src).unwrap(),
+///     Some((&b"ab\xC0c"[..], 8))
+/// );
+///
+/// let mut codec = SlipCodec::kiss(KissHeader::new(0, 0));
+/// let mut src = *b"\xC0\x10Hi\xC0";
+///
+/// assert_eq!(codec.decode(&mut src).unwrap(), Some((&b"Hi"[..], 5)));
+/// assert_eq!(codec.received_header().map(|header| header.port()), Some(1));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SlipCodec {
+    /// The framing mode.
+    mode: SlipMode,
+    /// The number of leading END bytes that have been skipped so far.
+    start: usize,
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+    /// The header of the last decoded KISS frame.
+    received_header: Option<KissHeader>,
+}
+
+impl SlipCodec {
+    /// Creates a new [`SlipCodec`] for plain SLIP frames.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::new_with_mode(SlipMode::Slip)
+    }
+
+    /// Creates a new [`SlipCodec`] for KISS frames, encoding frames with the given `header`.
+    #[inline]
+    pub const fn kiss(header: KissHeader) -> Self {
+        Self::new_with_mode(SlipMode::Kiss(header))
+    }
+
+    /// Creates a new [`SlipCodec`] with the given `mode`.
+    #[inline]
+    pub const fn new_with_mode(mode: SlipMode) -> Self {
+        Self {
+            mode,
+            start: 0,
+            seen: 0,
+            received_header: None,
+        }
+    }
+
+    /// Returns the framing mode.
+    #[inline]
+    pub const fn mode(&self) -> SlipMode {
+        self.mode
+    }
+
+    /// Returns the header of the last decoded KISS frame.
+    ///
+    /// Always `None` for plain SLIP frames.
+    #[inline]
+    pub const fn received_header(&self) -> Option<KissHeader> {
+        self.received_header
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.seen = 0;
+    }
+}
+
+/// An error that can occur when decoding a sequence of SLIP encoded bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlipDecodeError {
+    /// An ESC byte is not followed by ESC_END or ESC_ESC.
+    InvalidEscape,
+    /// The decoded payload does not fit into the item of a [`SlipCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for SlipDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidEscape => write!(f, "Invalid escape sequence"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SlipDecodeError {}
+
+/// Unescapes the SLIP encoded `frame` in place, returning the length of the decoded bytes.
+///
+/// `frame` must not contain the END byte.
+fn unescape(frame: &mut [u8]) -> Result<usize, SlipDecodeError> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < frame.len() {
+        let byte = match frame[read] {
+            SLIP_ESC => {
+                read += 1;
+
+                match frame.get(read) {
+                    Some(&SLIP_ESC_END) => SLIP_END,
+                    Some(&SLIP_ESC_ESC) => SLIP_ESC,
+                    _ => return Err(SlipDecodeError::InvalidEscape),
+                }
+            }
+            byte => byte,
+        };
+
+        frame[write] = byte;
+        read += 1;
+        write += 1;
+    }
+
+    Ok(write)
+}
+
+impl<'buf> Decoder<'buf> for SlipCodec {
+    type Item = &'buf [u8];
+    type Error = SlipDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        while self.seen < src.len() {
+            if src[self.seen] != SLIP_END {
+                self.seen += 1;
+
+                continue;
+            }
+
+            // A leading END byte or an empty frame.
+            if self.seen == self.start {
+                self.start += 1;
+                self.seen += 1;
+
+                continue;
+            }
+
+            let start = self.start;
+            let end = self.seen;
+
+            self.clear();
+
+            let len = unescape(&mut src[start..end])?;
+            let src: &'buf [u8] = src;
+            let mut bytes = &src[start..start + len];
+
+            if let SlipMode::Kiss(_) = self.mode {
+                // A non-empty frame unescapes to at least one byte.
+                self.received_header = Some(KissHeader::from_byte(bytes[0]));
+
+                bytes = &bytes[1..];
+            }
+
+            return Ok(Some((bytes, end + 1)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of SLIP encoded bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlipEncodeError {
+    /// The input buffer is too small to fit the encoded sequence of bytes.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for SlipEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SlipEncodeError {}
+
+impl BufferTooSmallError for SlipEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+/// Returns the number of bytes `byte` takes once escaped.
+#[inline]
+const fn escaped_len(byte: u8) -> usize {
+    match byte {
+        SLIP_END | SLIP_ESC => 2,
+        _ => 1,
+    }
+}
+
+/// Writes the escaped `byte` into `dst` at `index`, returning the index after it.
+#[inline]
+fn write_escaped(byte: u8, dst: &mut [u8], index: usize) -> usize {
+    match byte {
+        SLIP_END => {
+            dst[index] = SLIP_ESC;
+            dst[index + 1] = SLIP_ESC_END;
+        }
+        SLIP_ESC => {
+            dst[index] = SLIP_ESC;
+            dst[index + 1] = SLIP_ESC_ESC;
+        }
+        byte => dst[index] = byte,
+    }
+
+    index + escaped_len(byte)
+}
+
+impl Encoder<&[u8]> for SlipCodec {
+    type Error = SlipEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let header = match self.mode {
+            SlipMode::Slip => None,
+            SlipMode::Kiss(header) => Some(header.byte()),
+        };
+
+        let size = 2
+            + header.map_or(0, escaped_len)
+            + item.iter().map(|byte| escaped_len(*byte)).sum::<usize>();
+
+        if dst.len() < size {
+            return Err(SlipEncodeError::BufferTooSmall);
+        }
+
+        // The leading END byte flushes any line noise on the receiving side.
+        dst[0] = SLIP_END;
+
+        let mut index = 1;
+
+        for byte in header.iter().chain(item) {
+            index = write_escaped(*byte, dst, index);
+        }
+
+        dst[index] = SLIP_END;
+
+        Ok(index + 1)
+    }
+}
+
+impl EncoderEof for SlipCodec {
+    type Error = SlipEncodeError;
+}
+
+/// An owned [`SlipCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SlipCodecOwned<const N: usize> {
+    inner: SlipCodec,
+}
+
+impl<const N: usize> SlipCodecOwned<N> {
+    /// Creates a new [`SlipCodecOwned`] for plain SLIP frames.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: SlipCodec::new(),
+        }
+    }
+
+    /// Creates a new [`SlipCodecOwned`] for KISS frames, encoding frames with the given `header`.
+    #[inline]
+    pub const fn kiss(header: KissHeader) -> Self {
+        Self {
+            inner: SlipCodec::kiss(header),
+        }
+    }
+
+    /// Returns the framing mode.
+    #[inline]
+    pub const fn mode(&self) -> SlipMode {
+        self.inner.mode
+    }
+
+    /// Returns the header of the last decoded KISS frame.
+    ///
+    /// Always `None` for plain SLIP frames.
+    #[inline]
+    pub const fn received_header(&self) -> Option<KissHeader> {
+        self.inner.received_header
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.inner.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+impl<const N: usize> From<SlipCodec> for SlipCodecOwned<N> {
+    fn from(inner: SlipCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for SlipCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = SlipDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match Decoder::decode(&mut self.inner, src)? {
+            Some((bytes, size)) => {
+                let item = Vec::from_slice(bytes).map_err(|_| SlipDecodeError::Capacity)?;
+
+                Ok(Some((item, size)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for SlipCodecOwned<N> {
+    type Error = SlipEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for SlipCodecOwned<N> {
+    type Error = SlipEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+    };
+
+    use super::*;
+
+    #[test]
+    fn encode_escapes() {
+        let mut dst = [0_u8; 16];
+
+        let size = SlipCodec::new().encode(b"a\xC0b\xDBc", &mut dst).unwrap();
+
+        assert_eq!(b"\xC0a\xDB\xDCb\xDB\xDDc\xC0", &dst[..size]);
+
+        let size = SlipCodec::kiss(KissHeader::new(0x0C, 0x00))
+            .encode(b"a", &mut dst)
+            .unwrap();
+
+        assert_eq!(b"\xC0\xDB\xDCa\xC0", &dst[..size]);
+
+        assert_eq!(
+            Err(SlipEncodeError::BufferTooSmall),
+            SlipCodec::new().encode(b"a\xC0", &mut dst[..4])
+        );
+    }
+
+    #[test]
+    fn skip_leading_ends_and_empty_frames() {
+        init_tracing();
+
+        let mut codec = SlipCodec::new();
+        let mut src = *b"\xC0\xC0\xC0a\xDB\xDD\xC0\xC0\xC0b\xC0";
+
+        let (item, size) = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!((&b"a\xDB"[..], 7), (item, size));
+        assert_eq!(
+            Some((&b"b"[..], 4)),
+            codec.decode(&mut src[size..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn kiss() {
+        init_tracing();
+
+        let mut codec = SlipCodec::kiss(KissHeader::new(2, 0));
+        let mut dst = [0_u8; 16];
+
+        let size = codec.encode(b"\xC0Hi", &mut dst).unwrap();
+
+        assert_eq!(b"\xC0\x20\xDB\xDCHi\xC0", &dst[..size]);
+        assert_eq!(
+            Some((&b"\xC0Hi"[..], size)),
+            codec.decode(&mut dst[..size]).unwrap()
+        );
+
+        let header = codec.received_header().unwrap();
+
+        assert_eq!((2, 0), (header.port(), header.command()));
+
+        // A frame holding only the header decodes to an empty payload.
+        assert_eq!(
+            Some((&b""[..], 3)),
+            codec.decode(&mut { *b"\xC0\x01\xC0" }).unwrap()
+        );
+        assert_eq!(Some(1), codec.received_header().map(|h| h.command()));
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        init_tracing();
+
+        let mut codec = SlipCodec::new();
+
+        assert_eq!(
+            Err(SlipDecodeError::InvalidEscape),
+            codec.decode(&mut { *b"a\xDBb\xC0" })
+        );
+        assert_eq!(
+            Err(SlipDecodeError::InvalidEscape),
+            codec.decode(&mut { *b"a\xDB\xC0" })
+        );
+
+        // The codec recovers at the next frame.
+        assert_eq!(Ok(Some((&b"a"[..], 2))), codec.decode(&mut { *b"a\xC0" }));
+
+        let mut codec = SlipCodecOwned::<2>::new();
+
+        assert_eq!(
+            Err(SlipDecodeError::Capacity),
+            codec.decode_owned(&mut { *b"abc\xC0" })
+        );
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        assert_codec_conformance_items::<32, _>(SlipCodecOwned::<32>::new(), &[b"\xC0\xDB\xC0"]);
+
+        // Empty payloads survive in KISS frames, which always carry the header.
+        assert_codec_conformance_items::<32, _>(
+            SlipCodecOwned::<32>::kiss(KissHeader::new(0x0C, 0x0D)),
+            &[b"\xC0\xDB\xC0", b""],
+        );
+
+        let input = b"\xC0Hello\xC0\xC0\xC0Hey\xDB\xDC\xC0How";
+        let expected: &[&[u8]] = &[b"Hello", b"Hey\xC0"];
+
+        assert_decoder_conformance_truncated(SlipCodecOwned::<32>::new(), input, expected);
+    }
+}
@@ -0,0 +1,499 @@
+//! STX/ETX codec for encoding and decoding DLE escaped bytes framed by STX and ETX bytes, with an optional block check character.
+
+use heapless::Vec;
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, Encoder, EncoderEof};
+
+/// The byte that starts a frame.
+pub const STX: u8 = 0x02;
+
+/// The byte that ends a frame.
+pub const ETX: u8 = 0x03;
+
+/// The byte that escapes [`STX`], [`ETX`] and itself inside a frame.
+pub const DLE: u8 = 0x10;
+
+/// The block check character following the ETX byte of a frame.
+///
+/// It covers the bytes after the STX byte up to and including the ETX byte, as transmitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockCheck {
+    /// No block check character.
+    #[default]
+    None,
+    /// The XOR of the bytes.
+    Xor,
+    /// The longitudinal redundancy check, the two's complement of the sum of the bytes.
+    Lrc,
+}
+
+impl BlockCheck {
+    /// Returns the size of the block check character in bytes.
+    #[inline]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Xor | Self::Lrc => 1,
+        }
+    }
+
+    /// Computes the block check character of `bytes`.
+    fn compute(&self, bytes: &[u8]) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Xor => bytes.iter().fold(0, |bcc, byte| bcc ^ byte),
+            Self::Lrc => bytes
+                .iter()
+                .fold(0_u8, |sum, byte| sum.wrapping_add(*byte))
+                .wrapping_neg(),
+        }
+    }
+}
+
+/// A codec that decodes a sequence of DLE escaped bytes framed by STX and ETX bytes into a sequence of bytes and encodes a sequence of bytes into a sequence of DLE escaped bytes framed by STX and ETX bytes.
+///
+/// Decoding happens in place inside the buffer given to [`Decoder::decode`], the returned item borrows the unescaped bytes.
+/// Bytes before the STX byte are discarded, they are consumed together with the frame.
+/// An unescaped STX byte inside a frame starts a new frame, discarding the bytes before it.
+///
+/// A frame that fails to decode is skipped by the next call with the same buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{BlockCheck, Decoder, StxEtxCodec};
+///
+/// let mut codec = StxEtxCodec::new().with_block_check(BlockCheck::Xor);
+/// let mut src = *b"garbage\x02a\x10\x03b\x03\x13";
+///
+/// assert_eq!(
+///     codec.decode(&mut src).unwrap(),
+///     Some((&b"a\x03b"[..], 14))
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StxEtxCodec {
+    /// The block check character following the ETX byte.
+    block_check: BlockCheck,
+    /// The index of the STX byte of the current frame, if one was seen.
+    start: Option<usize>,
+    /// The last byte seen was an unescaped DLE byte.
+    escaped: bool,
+    /// The number of bytes of the slice that have been seen so far.
+    seen: usize,
+}
+
+impl StxEtxCodec {
+    /// Creates a new [`StxEtxCodec`] without a block check character.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            block_check: BlockCheck::None,
+            start: None,
+            escaped: false,
+            seen: 0,
+        }
+    }
+
+    /// Sets the block check character following the ETX byte.
+    #[inline]
+    pub const fn with_block_check(mut self, block_check: BlockCheck) -> Self {
+        self.block_check = block_check;
+        self
+    }
+
+    /// Returns the block check character following the ETX byte.
+    #[inline]
+    pub const fn block_check(&self) -> BlockCheck {
+        self.block_check
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.skip(0);
+    }
+
+    /// Skips the first `size` bytes of the next slice.
+    #[inline]
+    fn skip(&mut self, size: usize) {
+        self.start = None;
+        self.escaped = false;
+        self.seen = size;
+    }
+}
+
+/// An error that can occur when decoding a sequence of DLE escaped bytes framed by STX and ETX bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StxEtxDecodeError {
+    /// A DLE byte is not followed by STX, ETX or DLE.
+    InvalidEscape,
+    /// The block check character does not match the frame.
+    BlockCheck,
+    /// The decoded payload does not fit into the item of a [`StxEtxCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for StxEtxDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidEscape => write!(f, "Invalid escape sequence"),
+            Self::BlockCheck => write!(f, "Block check character mismatch"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StxEtxDecodeError {}
+
+/// Unescapes the DLE escaped `payload` in place, returning the length of the decoded bytes.
+///
+/// `payload` must not end with an unescaped DLE byte.
+fn unescape(payload: &mut [u8]) -> Result<usize, StxEtxDecodeError> {
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < payload.len() {
+        if payload[read] == DLE {
+            read += 1;
+
+            if !matches!(payload[read], STX | ETX | DLE) {
+                return Err(StxEtxDecodeError::InvalidEscape);
+            }
+        }
+
+        payload[write] = payload[read];
+        read += 1;
+        write += 1;
+    }
+
+    Ok(write)
+}
+
+impl<'buf> Decoder<'buf> for StxEtxCodec {
+    type Item = &'buf [u8];
+    type Error = StxEtxDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        while self.seen < src.len() {
+            let byte = src[self.seen];
+
+            let start = match self.start {
+                Some(_) if self.escaped => {
+                    self.escaped = false;
+                    self.seen += 1;
+
+                    continue;
+                }
+                Some(start) => start,
+                None => {
+                    if byte == STX {
+                        self.start = Some(self.seen);
+                    }
+
+                    self.seen += 1;
+
+                    continue;
+                }
+            };
+
+            match byte {
+                DLE => self.escaped = true,
+                STX => self.start = Some(self.seen),
+                ETX => {
+                    let end = self.seen;
+                    let size = end + 1 + self.block_check.size();
+
+                    // Wait for the block check character, the ETX byte is seen again on the next call.
+                    if src.len() < size {
+                        return Ok(None);
+                    }
+
+                    // The frame is consumed with the next one if it fails to decode.
+                    self.skip(size);
+
+                    if self.block_check != BlockCheck::None
+                        && self.block_check.compute(&src[start + 1..=end]) != src[end + 1]
+                    {
+                        return Err(StxEtxDecodeError::BlockCheck);
+                    }
+
+                    let len = unescape(&mut src[start + 1..end])?;
+
+                    self.clear();
+
+                    let src: &'buf [u8] = src;
+
+                    return Ok(Some((&src[start + 1..start + 1 + len], size)));
+                }
+                _ => {}
+            }
+
+            self.seen += 1;
+        }
+
+        Ok(None)
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of DLE escaped bytes framed by STX and ETX bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StxEtxEncodeError {
+    /// The input buffer is too small to fit the encoded sequence of bytes.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for StxEtxEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StxEtxEncodeError {}
+
+impl BufferTooSmallError for StxEtxEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl Encoder<&[u8]> for StxEtxCodec {
+    type Error = StxEtxEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let escapes = item
+            .iter()
+            .filter(|byte| matches!(**byte, STX | ETX | DLE))
+            .count();
+
+        let size = 2 + item.len() + escapes + self.block_check.size();
+
+        if dst.len() < size {
+            return Err(StxEtxEncodeError::BufferTooSmall);
+        }
+
+        dst[0] = STX;
+
+        let mut index = 1;
+
+        for byte in item {
+            if matches!(*byte, STX | ETX | DLE) {
+                dst[index] = DLE;
+                index += 1;
+            }
+
+            dst[index] = *byte;
+            index += 1;
+        }
+
+        dst[index] = ETX;
+        index += 1;
+
+        if self.block_check != BlockCheck::None {
+            dst[index] = self.block_check.compute(&dst[1..index]);
+            index += 1;
+        }
+
+        Ok(index)
+    }
+}
+
+impl EncoderEof for StxEtxCodec {
+    type Error = StxEtxEncodeError;
+}
+
+/// An owned [`StxEtxCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StxEtxCodecOwned<const N: usize> {
+    inner: StxEtxCodec,
+}
+
+impl<const N: usize> StxEtxCodecOwned<N> {
+    /// Creates a new [`StxEtxCodecOwned`] without a block check character.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: StxEtxCodec::new(),
+        }
+    }
+
+    /// Sets the block check character following the ETX byte.
+    #[inline]
+    pub const fn with_block_check(mut self, block_check: BlockCheck) -> Self {
+        self.inner.block_check = block_check;
+        self
+    }
+
+    /// Returns the block check character following the ETX byte.
+    #[inline]
+    pub const fn block_check(&self) -> BlockCheck {
+        self.inner.block_check
+    }
+
+    /// Returns the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub const fn seen(&self) -> usize {
+        self.inner.seen
+    }
+
+    /// Clears the number of bytes of the slice that have been seen so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+impl<const N: usize> From<StxEtxCodec> for StxEtxCodecOwned<N> {
+    fn from(inner: StxEtxCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for StxEtxCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = StxEtxDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match Decoder::decode(&mut self.inner, src)? {
+            Some((bytes, size)) => match Vec::from_slice(bytes) {
+                Ok(item) => Ok(Some((item, size))),
+                Err(_) => {
+                    self.inner.skip(size);
+
+                    Err(StxEtxDecodeError::Capacity)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for StxEtxCodecOwned<N> {
+    type Error = StxEtxEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for StxEtxCodecOwned<N> {
+    type Error = StxEtxEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+    };
+
+    use super::*;
+
+    #[test]
+    fn encode_escapes() {
+        let mut dst = [0_u8; 16];
+
+        let size = StxEtxCodec::new()
+            .encode(b"a\x02\x03\x10b", &mut dst)
+            .unwrap();
+
+        assert_eq!(b"\x02a\x10\x02\x10\x03\x10\x10b\x03", &dst[..size]);
+
+        let size = StxEtxCodec::new()
+            .with_block_check(BlockCheck::Lrc)
+            .encode(b"\x01\x02", &mut dst)
+            .unwrap();
+
+        // 0x01 + 0x10 + 0x02 + 0x03 = 0x16
+        assert_eq!(b"\x02\x01\x10\x02\x03\xEA", &dst[..size]);
+
+        assert_eq!(
+            Err(StxEtxEncodeError::BufferTooSmall),
+            StxEtxCodec::new()
+                .with_block_check(BlockCheck::Xor)
+                .encode(b"a", &mut dst[..3])
+        );
+    }
+
+    #[test]
+    fn discard_garbage_and_resync() {
+        init_tracing();
+
+        let mut codec = StxEtxCodec::new();
+
+        // Garbage, an escaped ETX, and a frame whose ETX was lost.
+        let mut src = *b"\x03\x10xy\x02lost\x02a\x10\x03\x03";
+
+        assert_eq!(Ok(Some((&b"a\x03"[..], src.len()))), codec.decode(&mut src));
+
+        // The block check character is awaited.
+        let mut codec = StxEtxCodec::new().with_block_check(BlockCheck::Xor);
+
+        assert_eq!(Ok(None), codec.decode(&mut { *b"\x02a\x03" }));
+        assert_eq!(
+            Ok(Some((&b"a"[..], 4))),
+            codec.decode(&mut { *b"\x02a\x03\x62" })
+        );
+    }
+
+    #[test]
+    fn malformed_frames_are_skipped() {
+        init_tracing();
+
+        let mut codec = StxEtxCodec::new().with_block_check(BlockCheck::Xor);
+        let mut src = *b"\x02a\x03\x00\x02a\x10b\x03\x10\x02b\x03\x61";
+
+        assert_eq!(Err(StxEtxDecodeError::BlockCheck), codec.decode(&mut src));
+        assert_eq!(
+            Err(StxEtxDecodeError::InvalidEscape),
+            codec.decode(&mut src)
+        );
+        assert_eq!(Ok(Some((&b"b"[..], src.len()))), codec.decode(&mut src));
+
+        let mut codec = StxEtxCodecOwned::<2>::new();
+        let mut src = *b"\x02abc\x03\x02ab\x03";
+
+        assert_eq!(
+            Err(StxEtxDecodeError::Capacity),
+            codec.decode_owned(&mut src)
+        );
+        assert_eq!(
+            Ok(Some((heapless::Vec::from_slice(b"ab").unwrap(), src.len()))),
+            codec.decode_owned(&mut src)
+        );
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        for block_check in [BlockCheck::None, BlockCheck::Xor, BlockCheck::Lrc] {
+            assert_codec_conformance_items::<32, _>(
+                StxEtxCodecOwned::<32>::new().with_block_check(block_check),
+                &[b"\x02\x03\x10", b""],
+            );
+        }
+
+        let input = b"xx\x02Hello\x03\x02Hey\x10\x10\x03\x02How";
+        let expected: &[&[u8]] = &[b"Hello", b"Hey\x10"];
+
+        assert_decoder_conformance_truncated(StxEtxCodecOwned::<32>::new(), input, expected);
+    }
+}
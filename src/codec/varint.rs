@@ -0,0 +1,473 @@
+//! Varint length codec for encoding and decoding bytes with an unsigned LEB128 payload length prefix.
+
+use heapless::Vec;
+
+use crate::{BufferTooSmallError, Decoder, DecoderOwned, DecoderRef, Encoder, EncoderEof};
+
+/// The maximum size of an unsigned LEB128 encoded `u64` in bytes.
+pub const MAX_VARINT_SIZE: usize = 10;
+
+/// The decoding progress of the current frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum State {
+    /// Reading the prefix. `value` holds the bits of the first `size` bytes.
+    Prefix { value: u64, size: usize },
+    /// The prefix of `prefix_size` bytes was read and the payload is `payload_length` bytes long.
+    Payload {
+        prefix_size: usize,
+        payload_length: usize,
+    },
+}
+
+/// A codec that decodes a sequence of bytes with an unsigned LEB128 payload length prefix into a sequence of bytes and encodes a sequence of bytes into a sequence of bytes with a minimal unsigned LEB128 payload length prefix.
+///
+/// The prefix is decoded incrementally: the bytes of a partially received prefix are not decoded again.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{DecoderRef, VarintLengthCodec};
+///
+/// let mut codec = VarintLengthCodec::new().with_max_frame_length(1024);
+///
+/// assert_eq!(
+///     codec.decode_ref(b"\x05Hello").unwrap(),
+///     Some((&b"Hello"[..], 6))
+/// );
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VarintLengthCodec {
+    /// The maximum length of a payload.
+    max_frame_length: usize,
+    state: State,
+}
+
+impl Default for VarintLengthCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VarintLengthCodec {
+    /// Creates a new [`VarintLengthCodec`] without a maximum frame length.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            max_frame_length: usize::MAX,
+            state: State::Prefix { value: 0, size: 0 },
+        }
+    }
+
+    /// Sets the maximum length of a payload.
+    ///
+    /// Decoding fails with [`VarintDecodeError::FrameTooLarge`] as soon as a prefix claims a larger payload, before the payload is buffered.
+    #[inline]
+    pub const fn with_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    /// Returns the maximum length of a payload.
+    #[inline]
+    pub const fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Clears the decoding progress of the current frame.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.state = State::Prefix { value: 0, size: 0 };
+    }
+
+    /// Returns the size of the prefix and the length of the payload, or `None` if the prefix is incomplete.
+    fn read_prefix(&mut self, src: &[u8]) -> Result<Option<(usize, usize)>, VarintDecodeError> {
+        let (mut value, mut size) = match self.state {
+            State::Payload {
+                prefix_size,
+                payload_length,
+            } => return Ok(Some((prefix_size, payload_length))),
+            State::Prefix { value, size } => (value, size),
+        };
+
+        while let Some(byte) = src.get(size) {
+            let bits = (byte & 0x7F) as u64;
+
+            // The tenth byte holds the most significant bit of a u64 and must be the last one.
+            if size == MAX_VARINT_SIZE - 1 && (bits > 1 || byte & 0x80 != 0) {
+                return Err(VarintDecodeError::Overflow);
+            }
+
+            value |= bits << (7 * size);
+            size += 1;
+
+            if byte & 0x80 != 0 {
+                continue;
+            }
+
+            if size > 1 && *byte == 0 {
+                return Err(VarintDecodeError::OverlongEncoding);
+            }
+
+            let payload_length = match usize::try_from(value) {
+                Ok(payload_length) if payload_length <= self.max_frame_length => payload_length,
+                _ => return Err(VarintDecodeError::FrameTooLarge),
+            };
+
+            self.state = State::Payload {
+                prefix_size: size,
+                payload_length,
+            };
+
+            return Ok(Some((size, payload_length)));
+        }
+
+        self.state = State::Prefix { value, size };
+
+        Ok(None)
+    }
+}
+
+/// An error that can occur when decoding a sequence of bytes with an unsigned LEB128 payload length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VarintDecodeError {
+    /// The prefix does not fit into a `u64`.
+    Overflow,
+    /// The prefix is not minimal, it ends with a zero byte.
+    OverlongEncoding,
+    /// The payload is longer than the maximum frame length.
+    FrameTooLarge,
+    /// The decoded payload does not fit into the item of a [`VarintLengthCodecOwned`].
+    Capacity,
+}
+
+impl core::fmt::Display for VarintDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "Varint overflow"),
+            Self::OverlongEncoding => write!(f, "Overlong varint encoding"),
+            Self::FrameTooLarge => write!(f, "Frame too large"),
+            Self::Capacity => write!(f, "Capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VarintDecodeError {}
+
+impl<'buf> Decoder<'buf> for VarintLengthCodec {
+    type Item = &'buf [u8];
+    type Error = VarintDecodeError;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf> DecoderRef<'buf> for VarintLengthCodec {
+    type Item = &'buf [u8];
+    type Error = VarintDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        let (prefix_size, payload_length) = match self.read_prefix(src) {
+            Ok(Some(prefix)) => prefix,
+            Ok(None) => return Ok(None),
+            Err(err) => {
+                self.clear();
+
+                return Err(err);
+            }
+        };
+
+        // The cached prefix may be longer than a shorter slice passed after it was read.
+        match src.len().checked_sub(prefix_size) {
+            Some(remaining) if remaining >= payload_length => {}
+            _ => return Ok(None),
+        }
+
+        let packet_len = prefix_size + payload_length;
+        let item = (&src[prefix_size..packet_len], packet_len);
+
+        self.clear();
+
+        Ok(Some(item))
+    }
+}
+
+/// An error that can occur when encoding a sequence of bytes into a sequence of bytes with an unsigned LEB128 payload length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VarintEncodeError {
+    /// The input buffer is too small to fit the encoded sequence of bytes.
+    BufferTooSmall,
+    /// The payload is longer than the maximum frame length.
+    FrameTooLarge,
+}
+
+impl core::fmt::Display for VarintEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::FrameTooLarge => write!(f, "Frame too large"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VarintEncodeError {}
+
+impl BufferTooSmallError for VarintEncodeError {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+/// Writes `value` as a minimal unsigned LEB128 varint into `dst`, returning the number of bytes written.
+///
+/// Returns `None` if `dst` is too small.
+pub fn write_varint(mut value: u64, dst: &mut [u8]) -> Option<usize> {
+    let mut size = 0;
+
+    loop {
+        let byte = (value & 0x7F) as u8;
+
+        value >>= 7;
+
+        let slot = dst.get_mut(size)?;
+
+        size += 1;
+
+        if value == 0 {
+            *slot = byte;
+
+            return Some(size);
+        }
+
+        *slot = byte | 0x80;
+    }
+}
+
+impl Encoder<&[u8]> for VarintLengthCodec {
+    type Error = VarintEncodeError;
+
+    fn encode(&mut self, item: &[u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+        if item.len() > self.max_frame_length {
+            return Err(VarintEncodeError::FrameTooLarge);
+        }
+
+        let prefix_size =
+            write_varint(item.len() as u64, dst).ok_or(VarintEncodeError::BufferTooSmall)?;
+
+        let packet_len = prefix_size + item.len();
+
+        if dst.len() < packet_len {
+            return Err(VarintEncodeError::BufferTooSmall);
+        }
+
+        dst[prefix_size..packet_len].copy_from_slice(item);
+
+        Ok(packet_len)
+    }
+}
+
+impl EncoderEof for VarintLengthCodec {
+    type Error = VarintEncodeError;
+}
+
+/// An owned [`VarintLengthCodec`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VarintLengthCodecOwned<const N: usize> {
+    inner: VarintLengthCodec,
+}
+
+impl<const N: usize> VarintLengthCodecOwned<N> {
+    /// Creates a new [`VarintLengthCodecOwned`] with a maximum frame length of `N`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: VarintLengthCodec::new().with_max_frame_length(N),
+        }
+    }
+
+    /// Clears the decoding progress of the current frame.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+impl<const N: usize> From<VarintLengthCodec> for VarintLengthCodecOwned<N> {
+    fn from(inner: VarintLengthCodec) -> Self {
+        Self { inner }
+    }
+}
+
+impl<const N: usize> DecoderOwned for VarintLengthCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = VarintDecodeError;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf, const N: usize> DecoderRef<'buf> for VarintLengthCodecOwned<N> {
+    type Item = Vec<u8, N>;
+    type Error = VarintDecodeError;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        match DecoderRef::decode_ref(&mut self.inner, src)? {
+            Some((bytes, size)) => {
+                let item = Vec::from_slice(bytes).map_err(|_| VarintDecodeError::Capacity)?;
+
+                Ok(Some((item, size)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<const N: usize> Encoder<Vec<u8, N>> for VarintLengthCodecOwned<N> {
+    type Error = VarintEncodeError;
+
+    fn encode(&mut self, item: Vec<u8, N>, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        Encoder::encode(&mut self.inner, &item, dst)
+    }
+}
+
+impl<const N: usize> EncoderEof for VarintLengthCodecOwned<N> {
+    type Error = VarintEncodeError;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use crate::{
+        test::init_tracing,
+        testing::{assert_codec_conformance_items, assert_decoder_conformance_truncated},
+    };
+
+    use super::*;
+
+    #[test]
+    fn write_minimal_varints() {
+        let mut buf = [0_u8; MAX_VARINT_SIZE];
+
+        for (value, expected) in [
+            (0, &b"\x00"[..]),
+            (1, b"\x01"),
+            (127, b"\x7F"),
+            (128, b"\x80\x01"),
+            (300, b"\xAC\x02"),
+            (u64::MAX, b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x01"),
+        ] {
+            let size = write_varint(value, &mut buf).expect("Must fit");
+
+            assert_eq!(expected, &buf[..size]);
+        }
+
+        assert_eq!(None, write_varint(300, &mut buf[..1]));
+    }
+
+    #[test]
+    fn conformance() {
+        init_tracing();
+
+        let encoded = assert_codec_conformance_items::<256, _>(
+            VarintLengthCodecOwned::<256>::new(),
+            &[&[b'a'; 200], b""],
+        );
+
+        // The 200 bytes item following the shared items has a two bytes prefix.
+        assert_eq!(b"\x03Hey\xC8\x01", &encoded[28..34]);
+
+        let input = b"\x05Hello\x80\x01";
+        let expected: &[&[u8]] = &[b"Hello"];
+
+        assert_decoder_conformance_truncated(VarintLengthCodecOwned::<256>::new(), input, expected);
+    }
+
+    #[test]
+    fn incremental_prefix() {
+        init_tracing();
+
+        let mut codec = VarintLengthCodec::new();
+
+        assert_eq!(Ok(None), codec.decode_ref(b"\x80"));
+        assert_eq!(Ok(None), codec.decode_ref(b"\x80\x01"));
+        assert_eq!(
+            Ok(Some((&[0_u8; 128][..], 130))),
+            codec.decode_ref(&[&b"\x80\x01"[..], &[0_u8; 128]].concat())
+        );
+    }
+
+    #[test]
+    fn shorter_slice_after_prefix() {
+        init_tracing();
+
+        let mut codec = VarintLengthCodec::new();
+
+        // The largest payload length does not overflow the packet length.
+        assert_eq!(
+            Ok(None),
+            codec.decode_ref(b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x01")
+        );
+        codec.clear();
+
+        assert_eq!(Ok(None), codec.decode_ref(b"\x80\x01Hello"));
+        assert_eq!(Ok(None), codec.decode_ref(b"\x80"));
+        assert_eq!(Ok(None), codec.decode_ref(b""));
+        assert_eq!(
+            Ok(Some((&[0_u8; 128][..], 130))),
+            codec.decode_ref(&[&b"\x80\x01"[..], &[0_u8; 128]].concat())
+        );
+    }
+
+    #[test]
+    fn hostile_prefixes() {
+        init_tracing();
+
+        let mut codec = VarintLengthCodec::new();
+
+        assert_eq!(
+            Err(VarintDecodeError::OverlongEncoding),
+            codec.decode_ref(b"\x85\x00Hello")
+        );
+
+        assert_eq!(
+            Err(VarintDecodeError::Overflow),
+            codec.decode_ref(b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x02")
+        );
+
+        assert_eq!(
+            Err(VarintDecodeError::Overflow),
+            codec.decode_ref(b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x81\x00")
+        );
+
+        // The state is cleared after an error.
+        assert_eq!(Ok(Some((&b"Hey"[..], 4))), codec.decode_ref(b"\x03Hey"));
+
+        let mut codec = VarintLengthCodec::new().with_max_frame_length(1024);
+
+        assert_eq!(
+            Err(VarintDecodeError::FrameTooLarge),
+            codec.decode_ref(b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x01")
+        );
+
+        assert_eq!(
+            Err(VarintEncodeError::FrameTooLarge),
+            codec.encode(&[0_u8; 1025], &mut [0_u8; 2048])
+        );
+
+        let mut codec = VarintLengthCodecOwned::<4>::new();
+
+        assert_eq!(
+            Err(VarintDecodeError::FrameTooLarge),
+            codec.decode_ref(b"\x05Hello")
+        );
+    }
+}
@@ -75,3 +75,43 @@ where
         (*self).decode_eof_owned(src)
     }
 }
+
+/// A decoder that decodes a frame from an immutable buffer.
+///
+/// Used to decode frames directly from the internal buffer of an [`AsyncBufRead`](crate::io::AsyncBufRead).
+pub trait DecoderRef<'buf> {
+    /// The type of item that this decoder decodes.
+    type Item;
+    /// The type of error that this decoder returns.
+    type Error;
+
+    /// Decodes a frame from the provided buffer.
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error>;
+
+    /// Decodes a frame from the provided buffer at the end of the stream.
+    fn decode_eof_ref(
+        &mut self,
+        src: &'buf [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_ref(src)
+    }
+}
+
+impl<'buf, D> DecoderRef<'buf> for &mut D
+where
+    D: DecoderRef<'buf>,
+{
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode_ref(&mut self, src: &'buf [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        (*self).decode_ref(src)
+    }
+
+    fn decode_eof_ref(
+        &mut self,
+        src: &'buf [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        (*self).decode_eof_ref(src)
+    }
+}
@@ -1,6 +1,6 @@
 //! Packet codec implementation module.
 
-use crate::{Decoder, Encoder};
+use crate::{Decoder, Encoder, EncoderEof};
 
 use super::packet::{Packet, PacketFromSliceError, PacketWriteError};
 
@@ -32,6 +32,10 @@ impl<'buf> Encoder<Packet<'buf>> for PacketCodec {
     }
 }
 
+impl EncoderEof for PacketCodec {
+    type Error = PacketWriteError;
+}
+
 #[cfg(test)]
 mod test {
     extern crate std;
@@ -65,8 +65,10 @@ impl Header {
     }
 
     /// Theoretical payload length. Calculated from [`Self::packet_length`] and [`Self::size`].
+    ///
+    /// Returns `0` if the packet length is smaller than the header size.
     pub const fn payload_length(&self) -> usize {
-        self.packet_length.get() as usize - Self::size()
+        self.packet_length_usize().saturating_sub(Self::size())
     }
 
     /// Returns the checksum.
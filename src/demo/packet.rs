@@ -111,4 +111,19 @@ mod test {
         assert_eq!(written, read);
         assert_eq!(reconstructed, packet);
     }
+
+    #[test]
+    fn packet_length_smaller_than_header() {
+        let buf = &mut [0; 100];
+
+        // Packet length of 1, payload type and checksum of 0.
+        buf[1] = 1;
+
+        assert!(matches!(
+            Packet::maybe_packet_from_prefix(buf),
+            Err(PacketFromSliceError::RawPacket(
+                RawPacketFromSliceError::PacketLength
+            ))
+        ));
+    }
 }
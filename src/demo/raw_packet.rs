@@ -32,7 +32,7 @@ impl RawPacket {
 
     /// Theoretical payload length as per the header.
     pub const fn payload_length(&self) -> usize {
-        self.header.packet_length() as usize - Header::size()
+        self.header.payload_length()
     }
 
     /// Writes the given payload to the given destination buffer.
@@ -67,6 +67,10 @@ impl RawPacket {
                 let packet_length = header.packet_length_usize();
                 let payload_length = header.payload_length();
 
+                if packet_length < Header::size() {
+                    return Err(RawPacketFromSliceError::PacketLength);
+                }
+
                 if rest.len() < payload_length {
                     return Ok(None);
                 }
@@ -104,4 +108,6 @@ pub enum RawPacketWriteError {
 pub enum RawPacketFromSliceError {
     /// Invalid checksum.
     Checksum,
+    /// The packet length is smaller than the header size.
+    PacketLength,
 }
@@ -0,0 +1,604 @@
+//! Fixed-capacity, bidirectional in-memory pipe.
+//!
+//! A [`Duplex`] holds one buffer per direction and is split into two connected [`DuplexStream`]s.
+//! Both streams implement [`AsyncRead`] and [`AsyncWrite`] without an allocator or an executor specific runtime,
+//! so framers can be exercised in `no_std` tests and examples.
+//!
+//! The streams share their buffers through a [`RefCell`], so they can only be driven from a single thread.
+
+use core::{
+    cell::RefCell,
+    future::poll_fn,
+    task::{Poll, Waker},
+};
+
+use heapless::Deque;
+
+use crate::io::{AsyncRead, AsyncWrite};
+
+/// An error that can occur while reading from or writing to a [`DuplexStream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DuplexError {
+    /// An error was injected at the current offset.
+    ///
+    /// See [`DuplexStream::with_read_error_at`] and [`DuplexStream::with_write_error_at`].
+    Injected,
+    /// The other stream was dropped, so the written bytes can never be read.
+    Closed,
+}
+
+impl core::fmt::Display for DuplexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Injected => write!(f, "Injected error"),
+            Self::Closed => write!(f, "Closed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DuplexError {}
+
+#[cfg(feature = "std")]
+impl From<DuplexError> for std::io::Error {
+    fn from(err: DuplexError) -> Self {
+        match err {
+            DuplexError::Injected => std::io::Error::other(err),
+            DuplexError::Closed => std::io::Error::new(std::io::ErrorKind::BrokenPipe, err),
+        }
+    }
+}
+
+/// One direction of a [`Duplex`].
+#[derive(Debug)]
+struct Buffer<const N: usize> {
+    bytes: Deque<u8, N>,
+    /// Waker of the stream waiting for bytes.
+    reader: Option<Waker>,
+    /// Waker of the stream waiting for free space.
+    writer: Option<Waker>,
+    /// The writing stream was shut down or dropped.
+    write_closed: bool,
+    /// The reading stream was dropped.
+    read_closed: bool,
+}
+
+impl<const N: usize> Buffer<N> {
+    const fn new() -> Self {
+        Self {
+            bytes: Deque::new(),
+            reader: None,
+            writer: None,
+            write_closed: false,
+            read_closed: false,
+        }
+    }
+
+    fn wake_reader(&mut self) {
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_writer(&mut self) {
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A fixed-capacity, bidirectional in-memory pipe.
+///
+/// Each direction can hold `N` bytes. See [`Duplex::split`].
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{duplex::Duplex, FramedRead, FramedWrite, LinesCodec, LinesCodecOwned};
+///
+/// # async fn run() {
+/// let duplex = Duplex::<8>::new();
+///
+/// let (left, right) = duplex.split();
+///
+/// let mut writer = FramedWrite::new_with_buffer(LinesCodec::new(), left, [0_u8; 32]);
+/// let mut reader = FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), right.with_max_read(1), [0_u8; 32]);
+///
+/// let (sent, read) = futures::future::join(writer.send_frame(b"Hello"), reader.read_frame_owned()).await;
+///
+/// assert!(sent.is_ok());
+/// assert_eq!(read.unwrap(), b"Hello");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Duplex<const N: usize> {
+    left_to_right: RefCell<Buffer<N>>,
+    right_to_left: RefCell<Buffer<N>>,
+}
+
+impl<const N: usize> Default for Duplex<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Duplex<N> {
+    /// Creates a new empty [`Duplex`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            left_to_right: RefCell::new(Buffer::new()),
+            right_to_left: RefCell::new(Buffer::new()),
+        }
+    }
+
+    /// Returns the number of bytes each direction can hold.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Splits the pipe into two connected [`DuplexStream`]s.
+    ///
+    /// Bytes written to one stream are read from the other.
+    pub fn split(&self) -> (DuplexStream<'_, N>, DuplexStream<'_, N>) {
+        (
+            DuplexStream::new(&self.right_to_left, &self.left_to_right),
+            DuplexStream::new(&self.left_to_right, &self.right_to_left),
+        )
+    }
+}
+
+/// One end of a [`Duplex`].
+///
+/// - Reading waits until at least one byte is available and returns `0` (EOF) once the other stream is shut down or dropped and the buffer is drained.
+/// - Writing waits for free space in the buffer and fails with [`DuplexError::Closed`] once the other stream is dropped.
+///
+/// Dropping the stream shuts it down.
+#[derive(Debug)]
+pub struct DuplexStream<'a, const N: usize> {
+    read: &'a RefCell<Buffer<N>>,
+    write: &'a RefCell<Buffer<N>>,
+    /// Maximum number of bytes returned by a single read.
+    max_read: usize,
+    /// Number of bytes read so far.
+    read_offset: usize,
+    /// Number of bytes written so far.
+    write_offset: usize,
+    /// Read offset at which EOF is returned.
+    eof_at: Option<usize>,
+    /// Read offset at which an error is returned once.
+    read_error_at: Option<usize>,
+    /// Write offset at which an error is returned once.
+    write_error_at: Option<usize>,
+}
+
+impl<'a, const N: usize> DuplexStream<'a, N> {
+    const fn new(read: &'a RefCell<Buffer<N>>, write: &'a RefCell<Buffer<N>>) -> Self {
+        Self {
+            read,
+            write,
+            max_read: usize::MAX,
+            read_offset: 0,
+            write_offset: 0,
+            eof_at: None,
+            read_error_at: None,
+            write_error_at: None,
+        }
+    }
+
+    /// Limits the number of bytes returned by a single read to `max_read`.
+    ///
+    /// A `max_read` of `0` is treated as `1`.
+    #[inline]
+    pub fn with_max_read(mut self, max_read: usize) -> Self {
+        self.max_read = max_read.max(1);
+        self
+    }
+
+    /// Returns EOF once `offset` bytes were read, even if the other stream is still writing.
+    #[inline]
+    pub fn with_eof_at(mut self, offset: usize) -> Self {
+        self.eof_at = Some(offset);
+        self
+    }
+
+    /// Returns [`DuplexError::Injected`] once from the read that reaches `offset` read bytes.
+    ///
+    /// The bytes before `offset` are returned by the previous reads. Reading continues normally after the error.
+    #[inline]
+    pub fn with_read_error_at(mut self, offset: usize) -> Self {
+        self.read_error_at = Some(offset);
+        self
+    }
+
+    /// Returns [`DuplexError::Injected`] once from the write that reaches `offset` written bytes.
+    ///
+    /// The bytes before `offset` are written before the error is returned.
+    #[inline]
+    pub fn with_write_error_at(mut self, offset: usize) -> Self {
+        self.write_error_at = Some(offset);
+        self
+    }
+
+    /// Returns the number of bytes read so far.
+    #[inline]
+    pub const fn read_offset(&self) -> usize {
+        self.read_offset
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    pub const fn write_offset(&self) -> usize {
+        self.write_offset
+    }
+
+    /// Writes as many bytes of `buf` as fit into the buffer, waiting for free space if it is full.
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, DuplexError> {
+        poll_fn(|cx| {
+            let mut state = self.write.borrow_mut();
+
+            if state.read_closed {
+                return Poll::Ready(Err(DuplexError::Closed));
+            }
+
+            let mut written = 0;
+
+            for byte in buf {
+                if state.bytes.push_back(*byte).is_err() {
+                    break;
+                }
+
+                written += 1;
+            }
+
+            if written > 0 {
+                state.wake_reader();
+
+                return Poll::Ready(Ok(written));
+            }
+
+            state.writer = Some(cx.waker().clone());
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    fn close_write(&mut self) {
+        let mut state = self.write.borrow_mut();
+
+        state.write_closed = true;
+        state.wake_reader();
+    }
+}
+
+impl<const N: usize> Drop for DuplexStream<'_, N> {
+    fn drop(&mut self) {
+        self.close_write();
+
+        let mut state = self.read.borrow_mut();
+
+        state.read_closed = true;
+        state.wake_writer();
+    }
+}
+
+impl<const N: usize> AsyncRead for DuplexStream<'_, N> {
+    type Error = DuplexError;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        if self.read_error_at == Some(self.read_offset) {
+            self.read_error_at = None;
+
+            return Err(DuplexError::Injected);
+        }
+
+        let mut limit = buf.len().min(self.max_read);
+
+        for at in [self.eof_at, self.read_error_at].into_iter().flatten() {
+            if at >= self.read_offset {
+                limit = limit.min(at - self.read_offset);
+            }
+        }
+
+        if limit == 0 {
+            return Ok(0);
+        }
+
+        let read = poll_fn(|cx| {
+            let mut state = self.read.borrow_mut();
+
+            let mut read = 0;
+
+            while read < limit {
+                match state.bytes.pop_front() {
+                    Some(byte) => buf[read] = byte,
+                    None => break,
+                }
+
+                read += 1;
+            }
+
+            if read > 0 {
+                state.wake_writer();
+
+                return Poll::Ready(read);
+            }
+
+            if state.write_closed {
+                return Poll::Ready(0);
+            }
+
+            state.reader = Some(cx.waker().clone());
+
+            Poll::Pending
+        })
+        .await;
+
+        self.read_offset += read;
+
+        Ok(read)
+    }
+}
+
+impl<const N: usize> AsyncWrite for DuplexStream<'_, N> {
+    type Error = DuplexError;
+
+    async fn write_all<'a>(&'a mut self, mut buf: &'a [u8]) -> Result<(), Self::Error> {
+        let mut injected = false;
+
+        if let Some(at) = self.write_error_at {
+            if at >= self.write_offset && at - self.write_offset < buf.len() {
+                buf = &buf[..at - self.write_offset];
+                injected = true;
+            }
+        }
+
+        while !buf.is_empty() {
+            let written = self.write(buf).await?;
+
+            self.write_offset += written;
+
+            buf = &buf[written..];
+        }
+
+        if injected {
+            self.write_error_at = None;
+
+            return Err(DuplexError::Injected);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), Self::Error> {
+        self.close_write();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use futures::StreamExt;
+
+    use crate::{
+        test::init_tracing, FramedRead, FramedReadError, FramedWrite, FramedWriteError, LinesCodec,
+        LinesCodecOwned,
+    };
+
+    use super::*;
+
+    fn items() -> StdVec<heapless::Vec<u8, 32>> {
+        std::vec![
+            heapless::Vec::from_slice(b"Hello").unwrap(),
+            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
+            heapless::Vec::from_slice(b"Hei").unwrap(),
+            heapless::Vec::from_slice(b"sup").unwrap(),
+            heapless::Vec::from_slice(b"Hey").unwrap(),
+        ]
+    }
+
+    /// Sends all items and returns the stream without shutting it down.
+    async fn send_items<const N: usize>(stream: DuplexStream<'_, N>) -> DuplexStream<'_, N> {
+        let mut framed_write = FramedWrite::new_with_buffer(LinesCodec::new(), stream, [0_u8; 32]);
+
+        for item in items() {
+            framed_write
+                .send_frame(item.as_slice())
+                .await
+                .expect("Must send");
+        }
+
+        let (_, _, stream) = framed_write.into_parts();
+
+        stream
+    }
+
+    #[tokio::test]
+    async fn both_directions() {
+        init_tracing();
+
+        let duplex = Duplex::<4>::new();
+
+        let (left, right) = duplex.split();
+
+        let mut left = FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), left, [0_u8; 32]);
+        let mut right =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), right, [0_u8; 32]);
+
+        let (collected_left, collected_right) = futures::future::join(
+            async {
+                let mut collected = StdVec::new();
+
+                for item in items() {
+                    left.reader_mut()
+                        .write_all(&[item.as_slice(), b"\n"].concat())
+                        .await
+                        .expect("Must write");
+
+                    collected.push(left.read_frame_owned().await.expect("Must read"));
+                }
+
+                collected
+            },
+            async {
+                let mut collected = StdVec::new();
+
+                for _ in items() {
+                    let item = right.read_frame_owned().await.expect("Must read");
+
+                    right
+                        .reader_mut()
+                        .write_all(&[item.as_slice(), b"\n"].concat())
+                        .await
+                        .expect("Must write");
+
+                    collected.push(item);
+                }
+
+                collected
+            },
+        )
+        .await;
+
+        assert_eq!(items(), collected_left);
+        assert_eq!(items(), collected_right);
+    }
+
+    #[tokio::test]
+    async fn max_read() {
+        init_tracing();
+
+        let duplex = Duplex::<64>::new();
+
+        let (left, right) = duplex.split();
+
+        drop(send_items(left).await);
+
+        let mut right = right.with_max_read(3);
+        let mut buf = [0_u8; 32];
+
+        assert_eq!(3, right.read(&mut buf).await.expect("Must read"));
+        assert_eq!(b"Hel", &buf[..3]);
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), right, [0_u8; 32]);
+
+        let collected = framed_read.stream().collect::<StdVec<_>>().await;
+
+        assert_eq!(items().len() + 1, collected.len());
+        assert!(matches!(collected[0], Ok(ref item) if item == b"lo"));
+        assert!(matches!(collected[5], Err(FramedReadError::EOF)));
+    }
+
+    #[tokio::test]
+    async fn inject_eof() {
+        init_tracing();
+
+        let duplex = Duplex::<64>::new();
+
+        let (left, right) = duplex.split();
+
+        // The writing stream is kept alive, EOF can only come from the injection.
+        let _left = send_items(left).await;
+
+        let mut framed_read = FramedRead::new_with_buffer(
+            LinesCodecOwned::<32>::new(),
+            right.with_eof_at(9),
+            [0_u8; 32],
+        );
+
+        let collected = framed_read.stream().collect::<StdVec<_>>().await;
+
+        assert_eq!(2, collected.len());
+        assert!(matches!(collected[0], Ok(ref item) if item == b"Hello"));
+        assert!(matches!(
+            collected[1],
+            Err(FramedReadError::BytesRemainingOnStream)
+        ));
+    }
+
+    #[tokio::test]
+    async fn inject_read_error() {
+        init_tracing();
+
+        let duplex = Duplex::<64>::new();
+
+        let (left, right) = duplex.split();
+
+        let _left = send_items(left).await;
+
+        let mut framed_read = FramedRead::new_with_buffer(
+            LinesCodecOwned::<32>::new(),
+            right.with_read_error_at(9),
+            [0_u8; 32],
+        );
+
+        assert_eq!(b"Hello", &framed_read.read_frame_owned().await.unwrap()[..]);
+        assert!(matches!(
+            framed_read.read_frame_owned().await,
+            Err(FramedReadError::IO(DuplexError::Injected))
+        ));
+
+        // Reading continues after the injected error.
+        assert_eq!(
+            b"Hello, world!",
+            &framed_read.read_frame_owned().await.unwrap()[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn inject_write_error() {
+        init_tracing();
+
+        let duplex = Duplex::<64>::new();
+
+        let (left, mut right) = duplex.split();
+
+        let mut framed_write = FramedWrite::new_with_buffer(
+            LinesCodec::new(),
+            left.with_write_error_at(9),
+            [0_u8; 32],
+        );
+
+        framed_write.send_frame(b"Hello").await.expect("Must send");
+
+        assert!(matches!(
+            framed_write.send_frame(b"Hello, world!").await,
+            Err(FramedWriteError::IO(DuplexError::Injected))
+        ));
+
+        let mut buf = [0_u8; 32];
+
+        assert_eq!(9, right.read(&mut buf).await.expect("Must read"));
+        assert_eq!(b"Hello\r\nHe", &buf[..9]);
+    }
+
+    #[tokio::test]
+    async fn closed() {
+        init_tracing();
+
+        let duplex = Duplex::<4>::new();
+
+        let (mut left, right) = duplex.split();
+
+        drop(right);
+
+        assert_eq!(Err(DuplexError::Closed), left.write_all(b"Hello").await);
+
+        let mut buf = [0_u8; 4];
+
+        assert_eq!(Ok(0), left.read(&mut buf).await);
+    }
+}
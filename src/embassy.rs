@@ -0,0 +1,241 @@
+//! [`Crate's AsyncRead`](crate::io::AsyncRead) and [`Crate's AsyncWrite`](crate::io::AsyncWrite) for [`Embassy's Pipe`](embassy_sync::pipe::Pipe) and helpers connecting framers to [`Embassy's Channel`](embassy_sync::channel::Channel).
+//!
+//! - [`Embassy's Reader`](embassy_sync::pipe::Reader) implements [`Crate's AsyncRead`](crate::io::AsyncRead) and [`Crate's AsyncBufRead`](crate::io::AsyncBufRead).
+//! - [`Embassy's Writer`](embassy_sync::pipe::Writer) implements [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+//! - [`send_frames`] reads frames from a [`FramedRead`] and sends them to a [`Sender`].
+//! - [`receive_frames`] receives items from a [`Receiver`] and writes them to a [`FramedWrite`].
+
+use core::convert::Infallible;
+
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    channel::{Receiver, Sender},
+    pipe::{Reader, Writer},
+};
+
+use crate::{
+    debug,
+    decode::DecoderOwned,
+    encode::Encoder,
+    framed_read::{FramedRead, FramedReadError},
+    framed_write::{FramedWrite, FramedWriteError},
+    io::{AsyncBufRead, AsyncRead, AsyncWrite},
+    warn,
+};
+
+impl<M, const N: usize> AsyncRead for Reader<'_, M, N>
+where
+    M: RawMutex,
+{
+    type Error = Infallible;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        Ok(Reader::read(self, buf).await)
+    }
+}
+
+impl<M, const N: usize> AsyncBufRead for Reader<'_, M, N>
+where
+    M: RawMutex,
+{
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        Ok(Reader::fill_buf(self).await)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        Reader::consume(self, amt)
+    }
+}
+
+impl<M, const N: usize> AsyncWrite for Writer<'_, M, N>
+where
+    M: RawMutex,
+{
+    type Error = Infallible;
+
+    async fn write_all<'a>(&'a mut self, mut buf: &'a [u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            let n = Writer::write(self, buf).await;
+
+            buf = &buf[n..];
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Reads frames from `framed_read` and sends them to `sender` until the reader reaches EOF or an error occurs.
+///
+/// Waits for free space in the channel before reading the next frame.
+///
+/// Returns:
+/// - `Ok(())` if the reader reached EOF.
+/// - `Err(error)` if an error occurred while reading a frame.
+pub async fn send_frames<const N: usize, D, R, M, const C: usize>(
+    framed_read: &mut FramedRead<N, D, R>,
+    sender: Sender<'_, M, D::Item, C>,
+) -> Result<(), FramedReadError<R::Error, D::Error>>
+where
+    D: DecoderOwned,
+    R: AsyncRead,
+    M: RawMutex,
+{
+    loop {
+        match framed_read.read_frame_owned().await {
+            Ok(item) => {
+                sender.send(item).await;
+
+                debug!("Frame sent to channel");
+            }
+            Err(FramedReadError::EOF) => {
+                debug!("EOF. Stopped sending frames");
+
+                return Ok(());
+            }
+            Err(err) => {
+                warn!("Failed to read frame. Stopped sending frames");
+
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Receives items from `receiver` and writes them to `framed_write` until an error occurs.
+///
+/// Never returns unless an error occurred while writing a frame.
+pub async fn receive_frames<const N: usize, E, W, M, I, const C: usize>(
+    receiver: Receiver<'_, M, I, C>,
+    framed_write: &mut FramedWrite<N, E, W>,
+) -> FramedWriteError<W::Error, E::Error>
+where
+    E: Encoder<I>,
+    W: AsyncWrite,
+    M: RawMutex,
+{
+    loop {
+        let item = receiver.receive().await;
+
+        debug!("Frame received from channel");
+
+        if let Err(err) = framed_write.send_frame(item).await {
+            warn!("Failed to write frame. Stopped receiving frames");
+
+            return err;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use embassy_sync::{
+        blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel, pipe::Pipe,
+    };
+    use heapless::Vec;
+
+    use crate::{test::init_tracing, FramedBufRead, LinesCodec, LinesCodecOwned};
+
+    use super::*;
+
+    fn items() -> StdVec<Vec<u8, 32>> {
+        std::vec![
+            Vec::from_slice(b"Hello").unwrap(),
+            Vec::from_slice(b"Hello, world!").unwrap(),
+            Vec::from_slice(b"Hei").unwrap(),
+            Vec::from_slice(b"sup").unwrap(),
+            Vec::from_slice(b"Hey").unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn pipe_to_channel() {
+        init_tracing();
+
+        let mut pipe = Pipe::<CriticalSectionRawMutex, 8>::new();
+        let (reader, writer) = pipe.split();
+        let channel = Channel::<CriticalSectionRawMutex, Vec<u8, 32>, 2>::new();
+
+        let write = async {
+            let mut framed_write =
+                FramedWrite::new_with_buffer(LinesCodec::new(), writer, [0_u8; 32]);
+
+            for item in items() {
+                framed_write
+                    .send_frame(item.as_slice())
+                    .await
+                    .expect("Must send");
+            }
+        };
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), reader, [0_u8; 32]);
+
+        let pump = send_frames(&mut framed_read, channel.sender());
+
+        let collect = async {
+            let mut collected = StdVec::new();
+
+            for _ in 0..items().len() {
+                collected.push(channel.receive().await);
+            }
+
+            collected
+        };
+
+        let collected = tokio::select! {
+            _ = pump => unreachable!("The pipe never reaches EOF"),
+            ((), collected) = async { tokio::join!(write, collect) } => collected,
+        };
+
+        assert_eq!(items(), collected);
+    }
+
+    #[tokio::test]
+    async fn channel_to_pipe() {
+        init_tracing();
+
+        let mut pipe = Pipe::<CriticalSectionRawMutex, 8>::new();
+        let (reader, writer) = pipe.split();
+        let channel = Channel::<CriticalSectionRawMutex, Vec<u8, 32>, 2>::new();
+
+        let send = async {
+            for item in items() {
+                channel.send(item).await;
+            }
+        };
+
+        let mut framed_write =
+            FramedWrite::new_with_buffer(LinesCodecOwned::<32>::new(), writer, [0_u8; 32]);
+
+        let pump = receive_frames(channel.receiver(), &mut framed_write);
+
+        // Reads directly from the pipe's buffer.
+        let mut framed_read =
+            FramedBufRead::new_with_buffer(LinesCodecOwned::<32>::new(), reader, [0_u8; 32]);
+
+        let collect = async {
+            let mut collected = StdVec::new();
+
+            for _ in 0..items().len() {
+                collected.push(framed_read.read_frame_owned().await.expect("Must read"));
+            }
+
+            collected
+        };
+
+        let collected = tokio::select! {
+            err = pump => unreachable!("Must not fail: {err:?}"),
+            ((), collected) = async { tokio::join!(send, collect) } => collected,
+        };
+
+        assert_eq!(items(), collected);
+    }
+}
@@ -1,12 +1,15 @@
 //! Compatibility wrapper for [`Embedded-io-async's Read`](embedded_io_async::Read) and [`Embedded-io-async's Write`](embedded_io_async::Write).
 
-use crate::io::{AsyncRead as CrateRead, AsyncWrite as CrateWrite};
+use crate::io::{AsyncBufRead as CrateBufRead, AsyncRead as CrateRead, AsyncWrite as CrateWrite};
 use core::borrow::{Borrow, BorrowMut};
 
 /// Compatibility wrapper for [`Embedded-io-async's Read`](embedded_io_async::Read) and [`Embedded-io-async's Write`](embedded_io_async::Write).
 ///
 /// - Converts an [`Embedded-io-async's Read`](embedded_io_async::Read) into a [`Crate's AsyncRead`](crate::io::AsyncRead).
 /// - Converts an [`Embedded-io-async's Write`](embedded_io_async::Write) into a [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+///
+/// [`Embedded-io-async's Read`](embedded_io_async::Read) and [`Embedded-io-async's Write`](embedded_io_async::Write) never pin the inner value,
+/// so `!Unpin` transports can be used directly.
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Compat<R>(R);
@@ -72,7 +75,7 @@ const _: () = {
 
     impl<R> CrateRead for Compat<R>
     where
-        R: embedded_io_async::Read + Unpin,
+        R: embedded_io_async::Read,
     {
         type Error = <R as ErrorType>::Error;
         fn read<'a>(
@@ -83,9 +86,22 @@ const _: () = {
         }
     }
 
+    impl<R> CrateBufRead for Compat<R>
+    where
+        R: embedded_io_async::Read + embedded_io_async::BufRead,
+    {
+        fn fill_buf(&mut self) -> impl core::future::Future<Output = Result<&[u8], Self::Error>> {
+            self.0.fill_buf()
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.0.consume(amt)
+        }
+    }
+
     impl<W> CrateWrite for Compat<W>
     where
-        W: embedded_io_async::Write + Unpin,
+        W: embedded_io_async::Write,
     {
         type Error = <W as ErrorType>::Error;
 
@@ -99,5 +115,10 @@ const _: () = {
         fn flush(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
             self.0.flush()
         }
+
+        fn shutdown(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            // `embedded_io_async::Write` has no notion of closing, flushing is the best we can do.
+            self.0.flush()
+        }
     }
 };
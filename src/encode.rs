@@ -19,3 +19,49 @@ where
         (*self).encode(item, dst)
     }
 }
+
+/// An error of an [`Encoder`] that tells whether the destination buffer was too small for the encoded frame.
+///
+/// Adapters encoding into growable buffers retry with a larger buffer on such an error only,
+/// every other error is returned right away.
+pub trait BufferTooSmallError {
+    /// Returns `true` if the destination buffer was too small for the encoded frame.
+    fn is_buffer_too_small(&self) -> bool;
+}
+
+impl BufferTooSmallError for core::convert::Infallible {
+    fn is_buffer_too_small(&self) -> bool {
+        match *self {}
+    }
+}
+
+/// An encoder that encodes a closing frame into a buffer at the end of the stream.
+///
+/// The closing frame does not depend on the type of the encoded items, so this is a separate trait rather than a method of [`Encoder`]:
+/// [`Encoder`] is generic over the item, and a codec encoding several item types, e.g. `&[u8]` and [`heapless::Vec`],
+/// would otherwise have one `encode_eof` per item type, which callers like [`FramedWrite::close`](crate::FramedWrite::close)
+/// could only pick by naming an item type.
+pub trait EncoderEof {
+    /// The type of error that this encoder returns.
+    type Error;
+
+    /// Encodes a closing frame into the provided buffer at the end of the stream.
+    ///
+    /// The default implementation writes nothing.
+    fn encode_eof(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        let _ = dst;
+
+        Ok(0)
+    }
+}
+
+impl<E> EncoderEof for &mut E
+where
+    E: EncoderEof,
+{
+    type Error = E::Error;
+
+    fn encode_eof(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        (*self).encode_eof(dst)
+    }
+}
@@ -0,0 +1,787 @@
+//! Deterministic fault injection for any [`AsyncRead`] or [`AsyncWrite`].
+//!
+//! A [`Faulty`] wraps a transport and simulates a lossy link, so framers and codecs can be tested against corrupted streams.
+//! Faults are drawn from a seeded pseudo-random generator, so a failing test can be replayed with the same seed.
+//!
+//! Rates are given in per mille (‰): a rate of `10` applies a fault to one byte in a hundred on average.
+//! A rate of `0` disables the fault and a rate of `1000` or above applies it to every byte.
+
+use core::{future::poll_fn, task::Poll};
+
+use crate::io::{AsyncRead, AsyncWrite};
+
+/// An error that can occur while reading from or writing to a [`Faulty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultError<E> {
+    /// An error of the wrapped transport.
+    IO(E),
+    /// An error was injected at the current offset.
+    ///
+    /// See [`Faulty::with_read_error_at`] and [`Faulty::with_write_error_at`].
+    Injected,
+}
+
+impl<E> core::fmt::Display for FaultError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {}", err),
+            Self::Injected => write!(f, "Injected error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for FaultError<E> where E: std::error::Error {}
+
+#[cfg(feature = "std")]
+impl From<FaultError<std::io::Error>> for std::io::Error {
+    fn from(err: FaultError<std::io::Error>) -> Self {
+        match err {
+            FaultError::IO(err) => err,
+            FaultError::Injected => std::io::Error::other("Injected error"),
+        }
+    }
+}
+
+/// A xorshift64* pseudo-random generator.
+#[derive(Debug, Clone)]
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    const fn new(seed: u64) -> Self {
+        // The state of a xorshift generator must not be zero.
+        Self {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn next_u8(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    /// Returns `true` with a probability of `rate` per mille.
+    fn hit(&mut self, rate: u16) -> bool {
+        rate > 0 && (self.next_u64() % 1000) < rate as u64
+    }
+}
+
+/// What happens to a single byte.
+#[derive(Debug, Clone, Copy)]
+enum Fate {
+    Keep(u8),
+    Drop,
+    Duplicate(u8),
+    Insert(u8, u8),
+}
+
+/// The per mille rates of the faults applied to every byte.
+#[derive(Debug, Clone, Copy, Default)]
+struct Rates {
+    drop: u16,
+    duplicate: u16,
+    flip: u16,
+    insert: u16,
+    delay: u16,
+}
+
+/// A wrapper injecting deterministic faults into the bytes read from or written to `T`.
+///
+/// - Bytes can be dropped, duplicated, bit-flipped or preceded by a random byte.
+///   See [`Faulty::with_drop`], [`Faulty::with_duplicate`], [`Faulty::with_flip`] and [`Faulty::with_insert`].
+/// - Reads and writes can be split into chunks of at most [`Faulty::with_max_chunk`] bytes.
+/// - Reads and writes can be delayed by yielding to the executor. See [`Faulty::with_delay`].
+/// - An error or a premature EOF can be injected at a given offset.
+///   See [`Faulty::with_read_error_at`], [`Faulty::with_write_error_at`] and [`Faulty::with_eof_at`].
+///
+/// Offsets count the bytes of the wrapped transport, before any byte is dropped, duplicated or inserted.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::{fault::Faulty, FramedRead, FramedReadError, LinesCodecOwned};
+///
+/// # async fn run() {
+/// let input: &[u8] = b"Hello\nHello, world!\n";
+///
+/// let reader = Faulty::new(input, 42).with_max_chunk(1).with_flip(100);
+///
+/// let mut framed_read = FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), reader, [0_u8; 32]);
+///
+/// loop {
+///     match framed_read.read_frame_owned().await {
+///         // Frames might be corrupted.
+///         Ok(_frame) => {}
+///         Err(FramedReadError::EOF) => break,
+///         Err(_err) => break,
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Faulty<T> {
+    inner: T,
+    rng: Rng,
+    rates: Rates,
+    /// Maximum number of bytes of a single read or write of the wrapped transport.
+    max_chunk: usize,
+    /// A byte that did not fit into the previous read.
+    carry: Option<u8>,
+    /// Number of bytes read from the wrapped transport so far.
+    read_offset: usize,
+    /// Number of bytes written so far, before faults were applied.
+    write_offset: usize,
+    /// Read offset at which EOF is returned.
+    eof_at: Option<usize>,
+    /// Read offset at which an error is returned once.
+    read_error_at: Option<usize>,
+    /// Write offset at which an error is returned once.
+    write_error_at: Option<usize>,
+}
+
+impl<T> Faulty<T> {
+    /// Creates a new [`Faulty`] wrapping `inner` and drawing faults from `seed`.
+    ///
+    /// No faults are applied until configured.
+    #[inline]
+    pub const fn new(inner: T, seed: u64) -> Self {
+        Self {
+            inner,
+            rng: Rng::new(seed),
+            rates: Rates {
+                drop: 0,
+                duplicate: 0,
+                flip: 0,
+                insert: 0,
+                delay: 0,
+            },
+            max_chunk: usize::MAX,
+            carry: None,
+            read_offset: 0,
+            write_offset: 0,
+            eof_at: None,
+            read_error_at: None,
+            write_error_at: None,
+        }
+    }
+
+    /// Drops bytes at the given per mille `rate`.
+    #[inline]
+    pub fn with_drop(mut self, rate: u16) -> Self {
+        self.rates.drop = rate;
+        self
+    }
+
+    /// Duplicates bytes at the given per mille `rate`.
+    #[inline]
+    pub fn with_duplicate(mut self, rate: u16) -> Self {
+        self.rates.duplicate = rate;
+        self
+    }
+
+    /// Flips a random bit of bytes at the given per mille `rate`.
+    #[inline]
+    pub fn with_flip(mut self, rate: u16) -> Self {
+        self.rates.flip = rate;
+        self
+    }
+
+    /// Inserts a random byte before bytes at the given per mille `rate`.
+    #[inline]
+    pub fn with_insert(mut self, rate: u16) -> Self {
+        self.rates.insert = rate;
+        self
+    }
+
+    /// Delays reads and writes at the given per mille `rate` by yielding to the executor once.
+    ///
+    /// The delay does not depend on a timer, so it works with any executor.
+    #[inline]
+    pub fn with_delay(mut self, rate: u16) -> Self {
+        self.rates.delay = rate;
+        self
+    }
+
+    /// Limits a single read or write of the wrapped transport to `max_chunk` bytes.
+    ///
+    /// A `max_chunk` of `0` is treated as `1`.
+    #[inline]
+    pub fn with_max_chunk(mut self, max_chunk: usize) -> Self {
+        self.max_chunk = max_chunk.max(1);
+        self
+    }
+
+    /// Returns EOF once `offset` bytes were read, even if the wrapped transport has more bytes.
+    #[inline]
+    pub fn with_eof_at(mut self, offset: usize) -> Self {
+        self.eof_at = Some(offset);
+        self
+    }
+
+    /// Returns [`FaultError::Injected`] once from the read that reaches `offset` read bytes.
+    ///
+    /// The bytes before `offset` are returned by the previous reads. Reading continues normally after the error.
+    #[inline]
+    pub fn with_read_error_at(mut self, offset: usize) -> Self {
+        self.read_error_at = Some(offset);
+        self
+    }
+
+    /// Returns [`FaultError::Injected`] once from the write that reaches `offset` written bytes.
+    ///
+    /// The bytes before `offset` are written before the error is returned.
+    #[inline]
+    pub fn with_write_error_at(mut self, offset: usize) -> Self {
+        self.write_error_at = Some(offset);
+        self
+    }
+
+    /// Returns the number of bytes read from the wrapped transport so far.
+    #[inline]
+    pub const fn read_offset(&self) -> usize {
+        self.read_offset
+    }
+
+    /// Returns the number of bytes written so far, before faults were applied.
+    #[inline]
+    pub const fn write_offset(&self) -> usize {
+        self.write_offset
+    }
+
+    /// Returns a reference to the wrapped transport.
+    #[inline]
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped transport.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the [`Faulty`] and returns the wrapped transport.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns `true` if bytes can be duplicated or inserted.
+    const fn expands(&self) -> bool {
+        self.rates.duplicate > 0 || self.rates.insert > 0
+    }
+
+    /// Decides what happens to `byte`.
+    fn fate(&mut self, byte: u8) -> Fate {
+        if self.rng.hit(self.rates.drop) {
+            return Fate::Drop;
+        }
+
+        let byte = if self.rng.hit(self.rates.flip) {
+            byte ^ (1 << (self.rng.next_u8() % 8))
+        } else {
+            byte
+        };
+
+        if self.rng.hit(self.rates.duplicate) {
+            return Fate::Duplicate(byte);
+        }
+
+        if self.rng.hit(self.rates.insert) {
+            return Fate::Insert(self.rng.next_u8(), byte);
+        }
+
+        Fate::Keep(byte)
+    }
+
+    /// Yields to the executor once at the configured delay rate.
+    async fn maybe_delay(&mut self) {
+        if !self.rng.hit(self.rates.delay) {
+            return;
+        }
+
+        let mut yielded = false;
+
+        poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+
+            yielded = true;
+
+            cx.waker().wake_by_ref();
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<T> AsyncRead for Faulty<T>
+where
+    T: AsyncRead,
+{
+    type Error = FaultError<T::Error>;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(byte) = self.carry.take() {
+            buf[0] = byte;
+
+            return Ok(1);
+        }
+
+        self.maybe_delay().await;
+
+        loop {
+            if self.read_error_at == Some(self.read_offset) {
+                self.read_error_at = None;
+
+                return Err(FaultError::Injected);
+            }
+
+            // Leaves room in `buf` for every byte to be duplicated or preceded by an inserted byte.
+            let start = if self.expands() { buf.len() / 2 } else { 0 };
+
+            let mut limit = (buf.len() - start).min(self.max_chunk);
+
+            for at in [self.eof_at, self.read_error_at].into_iter().flatten() {
+                if at >= self.read_offset {
+                    limit = limit.min(at - self.read_offset);
+                }
+            }
+
+            if limit == 0 {
+                return Ok(0);
+            }
+
+            let read = self
+                .inner
+                .read(&mut buf[start..start + limit])
+                .await
+                .map_err(FaultError::IO)?;
+
+            if read == 0 {
+                return Ok(0);
+            }
+
+            self.read_offset += read;
+
+            // Writing never overtakes reading, since at most two bytes are written per read byte and `start` is at least the number of read bytes.
+            let mut written = 0;
+
+            for index in start..start + read {
+                let byte = buf[index];
+
+                let (first, second) = match self.fate(byte) {
+                    Fate::Keep(byte) => (Some(byte), None),
+                    Fate::Drop => (None, None),
+                    Fate::Duplicate(byte) => (Some(byte), Some(byte)),
+                    Fate::Insert(inserted, byte) => (Some(inserted), Some(byte)),
+                };
+
+                for byte in [first, second].into_iter().flatten() {
+                    match buf.get_mut(written) {
+                        Some(slot) => {
+                            *slot = byte;
+
+                            written += 1;
+                        }
+                        // Only the last byte read into a buffer of an odd size can overflow.
+                        None => self.carry = Some(byte),
+                    }
+                }
+            }
+
+            // Every read byte was dropped. Returning `0` would signal EOF.
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for Faulty<T>
+where
+    T: AsyncWrite,
+{
+    type Error = FaultError<T::Error>;
+
+    async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<(), Self::Error> {
+        let mut limit = buf.len();
+
+        if let Some(at) = self.write_error_at {
+            if at >= self.write_offset {
+                limit = limit.min(at - self.write_offset);
+            }
+        }
+
+        for chunk in buf[..limit].chunks(self.max_chunk) {
+            self.maybe_delay().await;
+
+            // Writes runs of untouched bytes at once and every faulty byte on its own.
+            let mut run = 0;
+
+            for (index, byte) in chunk.iter().enumerate() {
+                let fate = self.fate(*byte);
+
+                if let Fate::Keep(kept) = fate {
+                    if kept == *byte {
+                        continue;
+                    }
+                }
+
+                self.inner
+                    .write_all(&chunk[run..index])
+                    .await
+                    .map_err(FaultError::IO)?;
+
+                run = index + 1;
+
+                let (faulty, len) = match fate {
+                    Fate::Keep(byte) => ([byte, 0], 1),
+                    Fate::Drop => ([0, 0], 0),
+                    Fate::Duplicate(byte) => ([byte, byte], 2),
+                    Fate::Insert(inserted, byte) => ([inserted, byte], 2),
+                };
+
+                self.inner
+                    .write_all(&faulty[..len])
+                    .await
+                    .map_err(FaultError::IO)?;
+            }
+
+            self.inner
+                .write_all(&chunk[run..])
+                .await
+                .map_err(FaultError::IO)?;
+
+            self.write_offset += chunk.len();
+        }
+
+        if limit < buf.len() {
+            self.write_error_at = None;
+
+            return Err(FaultError::Injected);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().await.map_err(FaultError::IO)
+    }
+
+    async fn shutdown(&mut self) -> Result<(), Self::Error> {
+        self.inner.shutdown().await.map_err(FaultError::IO)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use crate::{
+        decode::DecoderOwned, test::init_tracing, AnyDelimiterCodecOwned, BytesCodecOwned,
+        FramedRead, FramedReadError, FramedWrite, FramedWriteError, LengthCodecOwned, LinesCodec,
+        LinesCodecOwned,
+    };
+
+    use super::*;
+
+    const LINES: &[u8] = b"Hello\nHello, world!\nHei\nsup\nHey\n";
+
+    const LENGTH: &[u8] = b"\x00\x00\x00\x05Hello\x00\x00\x00\x0DHello, world!\x00\x00\x00\x03Hei\x00\x00\x00\x03sup\x00\x00\x00\x03Hey";
+
+    /// Reads all bytes through `reader`.
+    async fn corrupt(mut reader: Faulty<&[u8]>) -> StdVec<u8> {
+        let mut corrupted = StdVec::new();
+        let mut buf = [0_u8; 16];
+
+        loop {
+            match reader.read(&mut buf).await.expect("Must read") {
+                0 => return corrupted,
+                n => corrupted.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    /// Reads frames until an error occurs.
+    async fn read_frames<D, R>(
+        framed_read: &mut FramedRead<64, D, R>,
+    ) -> (StdVec<D::Item>, FramedReadError<R::Error, D::Error>)
+    where
+        D: DecoderOwned,
+        R: AsyncRead,
+    {
+        let mut frames = StdVec::new();
+
+        loop {
+            match framed_read.read_frame_owned().await {
+                Ok(frame) => frames.push(frame),
+                Err(err) => return (frames, err),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn deterministic() {
+        init_tracing();
+
+        let untouched = corrupt(Faulty::new(LINES, 1)).await;
+
+        assert_eq!(LINES, untouched);
+
+        let faulty = |seed, max_chunk| {
+            Faulty::new(LINES, seed)
+                .with_max_chunk(max_chunk)
+                .with_drop(50)
+                .with_duplicate(50)
+                .with_flip(50)
+                .with_insert(50)
+        };
+
+        let corrupted = corrupt(faulty(1, 64)).await;
+
+        assert_ne!(LINES, corrupted);
+        assert_eq!(corrupted, corrupt(faulty(1, 1)).await);
+        assert_ne!(corrupted, corrupt(faulty(2, 64)).await);
+    }
+
+    #[tokio::test]
+    async fn read_into_single_byte_buffer() {
+        init_tracing();
+
+        // Every byte is duplicated, the second byte is carried into the next read.
+        let mut reader = Faulty::new(&b"ab"[..], 1).with_duplicate(1000);
+        let mut buf = [0_u8; 1];
+        let mut read = StdVec::new();
+
+        while reader.read(&mut buf).await.expect("Must read") == 1 {
+            read.push(buf[0]);
+        }
+
+        assert_eq!(b"aabb", read.as_slice());
+    }
+
+    #[tokio::test]
+    async fn chunks_and_delays_keep_frames() {
+        init_tracing();
+
+        let reader = Faulty::new(LINES, 1).with_max_chunk(1).with_delay(500);
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), reader, [0_u8; 64]);
+
+        let (frames, error) = read_frames(&mut framed_read).await;
+
+        assert_eq!(5, frames.len());
+        assert!(matches!(error, FramedReadError::EOF));
+    }
+
+    #[tokio::test]
+    async fn inject_read_error_and_eof() {
+        init_tracing();
+
+        let reader = Faulty::new(LINES, 1).with_read_error_at(8).with_eof_at(22);
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), reader, [0_u8; 64]);
+
+        let (frames, error) = read_frames(&mut framed_read).await;
+
+        assert_eq!(1, frames.len());
+        assert!(matches!(error, FramedReadError::IO(FaultError::Injected)));
+
+        // Reading continues after the error until the premature EOF cuts the third line.
+        let (frames, error) = read_frames(&mut framed_read).await;
+
+        assert_eq!(&[b"Hello, world!"], frames.as_slice());
+        assert!(matches!(error, FramedReadError::BytesRemainingOnStream));
+    }
+
+    #[tokio::test]
+    async fn inject_write_error() {
+        init_tracing();
+
+        let writer = Faulty::new(heapless::Vec::<u8, 64>::new(), 1).with_write_error_at(10);
+
+        let mut framed_write = FramedWrite::new_with_buffer(LinesCodec::new(), writer, [0_u8; 32]);
+
+        framed_write.send_frame(b"Hello").await.expect("Must send");
+
+        assert!(matches!(
+            framed_write.send_frame(b"Hello, world!").await,
+            Err(FramedWriteError::IO(FaultError::Injected))
+        ));
+
+        let (_, _, writer) = framed_write.into_parts();
+
+        assert_eq!(b"Hello\r\nHel", writer.inner().as_slice());
+    }
+
+    #[tokio::test]
+    async fn write_faults() {
+        init_tracing();
+
+        let mut writer = Faulty::new(heapless::Vec::<u8, 64>::new(), 1)
+            .with_max_chunk(3)
+            .with_duplicate(1000);
+
+        writer.write_all(b"Hey").await.expect("Must write");
+
+        assert_eq!(b"HHeeyy", writer.inner().as_slice());
+        assert_eq!(3, writer.write_offset());
+    }
+
+    /// A flipped byte inside a line corrupts that line only, the codec resynchronizes on the next newline.
+    #[tokio::test]
+    async fn lines_resynchronize_after_flip() {
+        init_tracing();
+
+        let faulty = |seed| Faulty::new(LINES, seed).with_flip(20);
+
+        // Finds a seed flipping a single byte of the first line.
+        let mut seed = 0;
+
+        loop {
+            let corrupted = corrupt(faulty(seed)).await;
+
+            let flipped: StdVec<usize> = (0..LINES.len())
+                .filter(|index| LINES[*index] != corrupted[*index])
+                .collect();
+
+            if flipped.len() == 1 && flipped[0] < 5 && corrupted[flipped[0]] != b'\n' {
+                break;
+            }
+
+            seed += 1;
+        }
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), faulty(seed), [0_u8; 64]);
+
+        let (frames, error) = read_frames(&mut framed_read).await;
+
+        assert_ne!(b"Hello", frames[0].as_slice());
+        assert_eq!(
+            &[&b"Hello, world!"[..], b"Hei", b"sup", b"Hey"],
+            &frames[1..]
+        );
+        assert!(matches!(error, FramedReadError::EOF));
+    }
+
+    /// A corrupted length prefix desynchronizes the codec for the rest of the stream.
+    #[tokio::test]
+    async fn length_desynchronizes_after_corrupted_prefix() {
+        init_tracing();
+
+        let faulty = |seed| Faulty::new(LENGTH, seed).with_flip(20);
+
+        // Finds a seed flipping a byte of the first length prefix.
+        let mut seed = 0;
+
+        loop {
+            let corrupted = corrupt(faulty(seed)).await;
+
+            if corrupted[..4] != LENGTH[..4] {
+                break;
+            }
+
+            seed += 1;
+        }
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LengthCodecOwned::<32>::new(), faulty(seed), [0_u8; 64]);
+
+        let (frames, error) = read_frames(&mut framed_read).await;
+
+        assert!(frames.iter().all(|frame| frame.as_slice() != b"Hello"));
+        assert!(matches!(
+            error,
+            FramedReadError::BufferTooSmall
+                | FramedReadError::BytesRemainingOnStream
+                | FramedReadError::Decode(())
+        ));
+    }
+
+    /// No fault makes a built-in codec panic or a [`FramedRead`] loop forever.
+    #[tokio::test]
+    async fn built_in_codecs_survive_faults() {
+        init_tracing();
+
+        let faulty = |input, seed| {
+            Faulty::new(input, seed)
+                .with_max_chunk(seed as usize % 7 + 1)
+                .with_drop(30)
+                .with_duplicate(30)
+                .with_flip(30)
+                .with_insert(30)
+                .with_delay(100)
+        };
+
+        for seed in 0..64 {
+            let mut framed_read = FramedRead::new_with_buffer(
+                LinesCodecOwned::<32>::new(),
+                faulty(LINES, seed),
+                [0_u8; 64],
+            );
+
+            let (frames, _) = read_frames(&mut framed_read).await;
+
+            assert!(frames.len() <= LINES.len() * 2);
+
+            let mut framed_read = FramedRead::new_with_buffer(
+                LengthCodecOwned::<32>::new(),
+                faulty(LENGTH, seed),
+                [0_u8; 64],
+            );
+
+            let (frames, _) = read_frames(&mut framed_read).await;
+
+            assert!(frames.len() <= LENGTH.len() * 2);
+
+            let mut framed_read = FramedRead::new_with_buffer(
+                AnyDelimiterCodecOwned::<32>::new(b"\n"),
+                faulty(LINES, seed),
+                [0_u8; 64],
+            );
+
+            let (frames, _) = read_frames(&mut framed_read).await;
+
+            assert!(frames.len() <= LINES.len() * 2);
+
+            let mut framed_read = FramedRead::new_with_buffer(
+                BytesCodecOwned::<64>::new(),
+                faulty(LINES, seed),
+                [0_u8; 64],
+            );
+
+            let (frames, _) = read_frames(&mut framed_read).await;
+
+            assert!(frames.len() <= LINES.len() * 2);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Framed buffered read stream. Transforms an [`AsyncBufRead`] into a stream of frames.
+//!
+//! Frames are decoded directly from the internal buffer of the [`AsyncBufRead`].
+//! Bytes are only copied into an own buffer, if a frame straddles the boundary of the reader's internal buffer.
+
+use futures::Stream;
+
+use crate::{
+    debug,
+    decode::DecoderRef,
+    error,
+    framed_read::{FramedReadError, ReadFrame},
+    io::AsyncBufRead,
+    trace, warn,
+};
+
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
+use crate::logging::formatter::Formatter;
+
+/// A framer that reads frames from an [`AsyncBufRead`] source and decodes them using a [`DecoderRef`].
+///
+/// The `N` sized buffer is only used for frames that straddle the boundary of the reader's internal buffer.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FramedBufRead<const N: usize, D, R> {
+    state: ReadFrame<N>,
+    /// Number of bytes that must be consumed from the reader before reading again.
+    consume: usize,
+    decoder: D,
+    reader: R,
+}
+
+impl<const N: usize, D, R> FramedBufRead<N, D, R> {
+    /// Creates a new [`FramedBufRead`] with the given `decoder` and `reader`.
+    #[inline]
+    pub fn new(decoder: D, reader: R) -> Self {
+        Self {
+            state: ReadFrame::new(),
+            consume: 0,
+            decoder,
+            reader,
+        }
+    }
+
+    /// Creates a new [`FramedBufRead`] with the given `decoder`, `reader`, and `buffer`.
+    #[inline]
+    pub fn new_with_buffer(decoder: D, reader: R, buffer: [u8; N]) -> Self {
+        Self {
+            state: ReadFrame::new_with_buffer(buffer),
+            consume: 0,
+            decoder,
+            reader,
+        }
+    }
+
+    /// Returns reference to the decoder.
+    #[inline]
+    pub const fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// Returns mutable reference to the decoder.
+    #[inline]
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+
+    /// Returns reference to the reader.
+    #[inline]
+    pub const fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns mutable reference to the reader.
+    ///
+    /// Bytes of the last decoded frame might not have been consumed from the reader yet.
+    #[inline]
+    pub fn reader_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns reference to the internal state.
+    #[inline]
+    pub const fn state(&self) -> &ReadFrame<N> {
+        &self.state
+    }
+
+    /// Tries to read a frame from the underlying reader.
+    ///
+    /// Returns:
+    /// - `Ok(None)` if the buffer is not framable. Call `read_frame` again to read more bytes.
+    /// - `Ok(Some(frame))` if a frame was successfully decoded. Call `read_frame` again to read more bytes.
+    /// - `Err(error)` if an error occurred. The caller should stop reading.
+    pub async fn read_frame<'this>(
+        &'this mut self,
+    ) -> Result<Option<D::Item>, FramedReadError<R::Error, D::Error>>
+    where
+        D: DecoderRef<'this>,
+        R: AsyncBufRead,
+    {
+        if self.consume > 0 {
+            self.reader.consume(self.consume);
+
+            debug!("Consumed from reader. bytes: {}", self.consume);
+
+            self.consume = 0;
+        }
+
+        if self.state.shift {
+            self.state
+                .buffer
+                .copy_within(self.state.total_consumed..self.state.index, 0);
+
+            self.state.index -= self.state.total_consumed;
+            self.state.total_consumed = 0;
+
+            debug!("Buffer shifted. copied: {}", self.state.framable());
+
+            self.state.shift = false;
+        }
+
+        if self.state.index > 0 {
+            debug!(
+                "total_consumed: {}, index: {}, buffer: {:?}",
+                self.state.total_consumed,
+                self.state.index,
+                Formatter(&self.state.buffer[self.state.total_consumed..self.state.index])
+            );
+
+            if self.state.is_framable {
+                let src = &self.state.buffer[self.state.total_consumed..self.state.index];
+
+                let result = match self.state.eof {
+                    true => {
+                        trace!("Framing buffered bytes on EOF");
+
+                        self.decoder.decode_eof_ref(src)
+                    }
+                    false => {
+                        trace!("Framing buffered bytes");
+
+                        self.decoder.decode_ref(src)
+                    }
+                };
+
+                match result {
+                    Ok(Some((item, size))) => {
+                        self.state.total_consumed += size;
+                        self.state.shift = true;
+
+                        debug!(
+                            "Frame decoded, consumed: {}, total_consumed: {}",
+                            size, self.state.total_consumed,
+                        );
+
+                        return Ok(Some(item));
+                    }
+                    Ok(None) => {
+                        debug!("No frame decoded");
+
+                        self.state.is_framable = false;
+
+                        if self.state.eof {
+                            error!("Bytes remaining on stream");
+
+                            return Err(FramedReadError::BytesRemainingOnStream);
+                        }
+
+                        return Ok(None);
+                    }
+                    Err(err) => {
+                        error!("Failed to decode frame");
+
+                        return Err(FramedReadError::Decode(err));
+                    }
+                }
+            }
+
+            if self.state.index >= self.state.buffer.len() {
+                error!("Buffer too small");
+
+                return Err(FramedReadError::BufferTooSmall);
+            }
+
+            trace!("Reading into buffer");
+
+            return match self.reader.fill_buf().await {
+                Err(err) => {
+                    error!("Failed to read");
+
+                    Err(FramedReadError::IO(err))
+                }
+                Ok([]) => {
+                    warn!("Got EOF");
+
+                    self.state.eof = true;
+                    self.state.is_framable = true;
+
+                    Ok(None)
+                }
+                Ok(buf) => {
+                    let n = core::cmp::min(buf.len(), self.state.buffer.len() - self.state.index);
+
+                    self.state.buffer[self.state.index..self.state.index + n]
+                        .copy_from_slice(&buf[..n]);
+
+                    debug!("Bytes copied. bytes: {}", n);
+
+                    self.state.index += n;
+                    self.state.is_framable = true;
+                    self.consume = n;
+
+                    Ok(None)
+                }
+            };
+        }
+
+        if self.state.eof {
+            return Err(FramedReadError::EOF);
+        }
+
+        trace!("Reading");
+
+        let buf = match self.reader.fill_buf().await {
+            Err(err) => {
+                error!("Failed to read");
+
+                return Err(FramedReadError::IO(err));
+            }
+            Ok([]) => {
+                warn!("Got EOF");
+
+                self.state.eof = true;
+
+                return Err(FramedReadError::EOF);
+            }
+            Ok(buf) => buf,
+        };
+
+        debug!("Framing reader's buffer: {:?}", Formatter(buf));
+
+        match self.decoder.decode_ref(buf) {
+            Ok(Some((item, size))) => {
+                self.consume = size;
+
+                debug!("Frame decoded, consumed: {}", size);
+
+                Ok(Some(item))
+            }
+            Ok(None) => {
+                // The frame straddles the boundary of the reader's buffer and is longer than the reader's bytes.
+                if buf.len() >= self.state.buffer.len() {
+                    error!("Buffer too small");
+
+                    return Err(FramedReadError::BufferTooSmall);
+                }
+
+                self.state.buffer[..buf.len()].copy_from_slice(buf);
+
+                debug!("No frame decoded. bytes copied: {}", buf.len());
+
+                self.state.index = buf.len();
+                self.state.total_consumed = 0;
+                self.state.is_framable = false;
+                self.consume = buf.len();
+
+                Ok(None)
+            }
+            Err(err) => {
+                error!("Failed to decode frame");
+
+                Err(FramedReadError::Decode(err))
+            }
+        }
+    }
+
+    /// Tries to read a frame from the underlying reader.
+    ///
+    /// Returns:
+    /// - `Ok(frame)` if a frame was successfully decoded. Call `read_frame_owned` again to read more bytes.
+    /// - `Err(error)` if an error occurred. The caller should stop reading.
+    pub async fn read_frame_owned<I, E>(&mut self) -> Result<I, FramedReadError<R::Error, E>>
+    where
+        D: for<'a> DecoderRef<'a, Item = I, Error = E>,
+        R: AsyncBufRead,
+    {
+        loop {
+            if let Some(item) = self.read_frame().await? {
+                return Ok(item);
+            }
+        }
+    }
+
+    /// Converts the [`FramedBufRead`] into a stream of frames.
+    pub fn stream<I, E>(
+        &mut self,
+    ) -> impl Stream<Item = Result<I, FramedReadError<R::Error, E>>> + '_
+    where
+        D: for<'a> DecoderRef<'a, Item = I, Error = E>,
+        R: AsyncBufRead,
+    {
+        futures::stream::unfold((self, false), |(this, errored)| async move {
+            if errored {
+                return None;
+            }
+
+            match this.read_frame_owned().await {
+                Ok(item) => Some((Ok(item), (this, false))),
+                Err(err) => Some((Err(err), (this, true))),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use futures::StreamExt;
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    use crate::{test::init_tracing, tokio::Compat, LengthCodecOwned, LinesCodec};
+
+    use super::*;
+
+    const LINES: &[&[u8]] = &[
+        b"Hel",
+        b"lo\n",
+        b"Hell",
+        b"o, world!\n",
+        b"H",
+        b"ei\r\n",
+        b"sup",
+        b"\n",
+        b"Hey\r",
+        b"\n",
+        b"How ",
+        b"are y",
+    ];
+
+    async fn collect_lines<const N: usize>(
+        capacity: usize,
+        duplex_max_size: usize,
+    ) -> (
+        Vec<Vec<u8>>,
+        FramedReadError<std::io::Error, core::convert::Infallible>,
+    ) {
+        let (read, mut write) = tokio::io::duplex(duplex_max_size);
+
+        tokio::spawn(async move {
+            for item in LINES {
+                write.write_all(item).await.expect("Must write");
+            }
+        });
+
+        let mut framer = FramedBufRead::<N, _, _>::new(
+            LinesCodec::new(),
+            Compat::new(BufReader::with_capacity(capacity, read)),
+        );
+
+        let mut collected = Vec::new();
+
+        loop {
+            match framer.read_frame().await {
+                Ok(Some(item)) => collected.push(item.to_vec()),
+                Ok(None) => {}
+                Err(err) => return (collected, err),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn framed_read() {
+        init_tracing();
+
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
+
+        for capacity in [1, 2, 4, 8, 16, 1024] {
+            for duplex_max_size in [1, 2, 4, 1024] {
+                let (collected, err) = collect_lines::<16>(capacity, duplex_max_size).await;
+
+                assert_eq!(expected, collected);
+                assert!(matches!(err, FramedReadError::BytesRemainingOnStream));
+            }
+        }
+
+        let (collected, err) = collect_lines::<4>(1, 1).await;
+
+        assert!(collected.is_empty());
+        assert!(matches!(err, FramedReadError::BufferTooSmall));
+    }
+
+    #[tokio::test]
+    async fn decodes_from_reader_buffer() {
+        init_tracing();
+
+        let bytes = b"Hello\nHello, world!\nHei\n";
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write.write_all(bytes).await.expect("Must write");
+        drop(write);
+
+        // A zero sized own buffer proves that no frame is copied.
+        let mut framer = FramedBufRead::<0, _, _>::new(
+            LinesCodec::new(),
+            Compat::new(BufReader::with_capacity(1024, read)),
+        );
+
+        let mut collected = Vec::new();
+
+        loop {
+            match framer.read_frame().await {
+                Ok(Some(item)) => collected.push(item.to_vec()),
+                Ok(None) => {}
+                Err(err) => {
+                    assert!(matches!(err, FramedReadError::EOF));
+
+                    break;
+                }
+            }
+        }
+
+        let expected: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei"];
+
+        assert_eq!(expected, collected);
+    }
+
+    #[tokio::test]
+    async fn straddling_frame_does_not_fit() {
+        init_tracing();
+
+        for (capacity, decoded) in [(4, 0), (6, 1)] {
+            let (read, mut write) = tokio::io::duplex(1024);
+
+            write
+                .write_all(b"Hello\nHello, world!\n")
+                .await
+                .expect("Must write");
+            drop(write);
+
+            let mut framer = FramedBufRead::<0, _, _>::new(
+                LinesCodec::new(),
+                Compat::new(BufReader::with_capacity(capacity, read)),
+            );
+
+            let mut collected = Vec::new();
+
+            let err = loop {
+                match framer.read_frame().await {
+                    Ok(Some(item)) => collected.push(item.to_vec()),
+                    Ok(None) => {}
+                    Err(err) => break err,
+                }
+            };
+
+            assert!(matches!(err, FramedReadError::BufferTooSmall));
+            assert_eq!(decoded, collected.len());
+        }
+
+        let (read, mut write) = tokio::io::duplex(1024);
+
+        write
+            .write_all(b"Hello, world!\n")
+            .await
+            .expect("Must write");
+        drop(write);
+
+        let mut framer = FramedBufRead::<13, _, _>::new(
+            LinesCodec::new(),
+            Compat::new(BufReader::with_capacity(13, read)),
+        );
+
+        let err = loop {
+            if let Err(err) = framer.read_frame().await {
+                break err;
+            }
+        };
+
+        assert!(matches!(err, FramedReadError::BufferTooSmall));
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        init_tracing();
+
+        let items: &[&[u8]] = &[
+            b"\x00\x00\x00\x05Hel",
+            b"lo\x00\x00",
+            b"\x00\x0DHell",
+            b"o, world!",
+            b"\x00",
+            b"\x00\x00\x03H",
+            b"ei",
+        ];
+
+        let (read, mut write) = tokio::io::duplex(4);
+
+        tokio::spawn(async move {
+            for item in items {
+                write.write_all(item).await.expect("Must write");
+            }
+        });
+
+        let mut framer = FramedBufRead::<32, _, _>::new(
+            LengthCodecOwned::<32>::new(),
+            Compat::new(BufReader::with_capacity(8, read)),
+        );
+
+        let collected = framer
+            .stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let expected: Vec<heapless::Vec<u8, 32>> = std::vec![
+            heapless::Vec::from_slice(b"Hello").unwrap(),
+            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
+            heapless::Vec::from_slice(b"Hei").unwrap(),
+        ];
+
+        assert_eq!(expected, collected);
+    }
+}
@@ -60,19 +60,19 @@ pub struct ReadFrame<const N: usize> {
     /// The current index in the buffer.
     ///
     /// Represents the number of bytes read into the buffer.
-    index: usize,
+    pub(crate) index: usize,
     /// EOF was reached while decoding.
-    eof: bool,
+    pub(crate) eof: bool,
     /// The buffer is currently framable.
-    is_framable: bool,
+    pub(crate) is_framable: bool,
     /// The buffer must be shifted before reading more bytes.
     ///
     /// Makes room for more bytes to be read into the buffer, keeping the already read bytes.
-    shift: bool,
+    pub(crate) shift: bool,
     /// Total number of bytes decoded in a framing round.
-    total_consumed: usize,
+    pub(crate) total_consumed: usize,
     /// The underlying buffer to read into.
-    buffer: [u8; N],
+    pub(crate) buffer: [u8; N],
 }
 
 impl<const N: usize> Default for ReadFrame<N> {
@@ -5,7 +5,12 @@ use futures::Sink;
 #[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
 use crate::logging::formatter::Formatter;
 
-use crate::{debug, encode::Encoder, io::AsyncWrite, warn};
+use crate::{
+    debug,
+    encode::{Encoder, EncoderEof},
+    io::AsyncWrite,
+    warn,
+};
 
 /// An error that can occur while writing a frame.
 #[derive(Debug)]
@@ -201,6 +206,56 @@ impl<const N: usize, E, W> FramedWrite<N, E, W> {
         }
     }
 
+    /// Writes the closing frame of the `encoder` (if any) to the underlying `writer`, flushes and shuts it down.
+    ///
+    /// See [`EncoderEof::encode_eof`] and [`AsyncWrite::shutdown`].
+    pub async fn close(&mut self) -> Result<(), FramedWriteError<W::Error, E::Error>>
+    where
+        E: EncoderEof,
+        W: AsyncWrite,
+    {
+        let size = match self.encoder.encode_eof(&mut self.state.buffer) {
+            Ok(size) => size,
+            Err(err) => {
+                warn!("Failed to encode closing frame");
+
+                return Err(FramedWriteError::Encode(err));
+            }
+        };
+
+        if size > 0 {
+            if let Err(err) = self.writer.write_all(&self.state.buffer[..size]).await {
+                warn!("Failed to write closing frame");
+
+                return Err(FramedWriteError::IO(err));
+            }
+
+            debug!(
+                "Wrote closing frame. buffer: {:?}",
+                Formatter(&self.state.buffer[..size])
+            );
+        }
+
+        if let Err(err) = self.writer.flush().await {
+            warn!("Failed to flush");
+
+            return Err(FramedWriteError::IO(err));
+        }
+
+        match self.writer.shutdown().await {
+            Ok(_) => {
+                debug!("Shut down");
+
+                Ok(())
+            }
+            Err(err) => {
+                warn!("Failed to shut down");
+
+                Err(FramedWriteError::IO(err))
+            }
+        }
+    }
+
     /// Converts the [`FramedWrite`] into a sink.
     pub fn sink<'this, I>(
         &'this mut self,
@@ -217,3 +272,82 @@ impl<const N: usize, E, W> FramedWrite<N, E, W> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use futures::StreamExt;
+
+    use crate::{
+        codec::lines::{LinesCodec, LinesEncodeError},
+        test::init_tracing,
+        tokio::Compat,
+        FramedRead, LinesCodecOwned,
+    };
+
+    use super::*;
+
+    /// A [`LinesCodec`] that writes an empty line as a closing frame.
+    struct TrailerCodec(LinesCodec);
+
+    impl<'a> Encoder<&'a [u8]> for TrailerCodec {
+        type Error = LinesEncodeError;
+
+        fn encode(&mut self, item: &'a [u8], dst: &mut [u8]) -> Result<usize, Self::Error> {
+            self.0.encode(item, dst)
+        }
+    }
+
+    impl EncoderEof for TrailerCodec {
+        type Error = LinesEncodeError;
+
+        fn encode_eof(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+            self.0.encode(b"", dst)
+        }
+    }
+
+    #[tokio::test]
+    async fn close() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(1024);
+
+        let mut writer = FramedWrite::new_with_buffer(
+            TrailerCodec(LinesCodec::new()),
+            Compat::new(write),
+            [0_u8; 64],
+        );
+
+        let mut reader = FramedRead::new_with_buffer(
+            LinesCodecOwned::<32>::new(),
+            Compat::new(read),
+            [0_u8; 64],
+        );
+
+        for item in [b"Hello".as_slice(), b"world"] {
+            writer.send_frame(item).await.expect("Must send");
+        }
+
+        writer.close().await.expect("Must close");
+
+        // The writer is still alive, EOF can only come from the shutdown.
+        let collected = reader
+            .stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let expected: Vec<heapless::Vec<u8, 32>> = std::vec![
+            heapless::Vec::from_slice(b"Hello").unwrap(),
+            heapless::Vec::from_slice(b"world").unwrap(),
+            heapless::Vec::new(),
+        ];
+
+        assert_eq!(expected, collected);
+    }
+}
@@ -1,7 +1,12 @@
 //! Compatibility wrapper for [`Futures-io' AsyncRead`](futures::io::AsyncRead) and [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
 
-use crate::io::{AsyncRead as CrateAsyncRead, AsyncWrite as CrateAsyncWrite};
-use core::borrow::{Borrow, BorrowMut};
+use crate::io::{
+    AsyncBufRead as CrateAsyncBufRead, AsyncRead as CrateAsyncRead, AsyncWrite as CrateAsyncWrite,
+};
+use core::{
+    borrow::{Borrow, BorrowMut},
+    pin::Pin,
+};
 
 /// Compatibility wrapper for [`Futures-io' AsyncRead`](futures::io::AsyncRead) and [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
 ///
@@ -68,7 +73,7 @@ impl<R> From<R> for Compat<R> {
 }
 
 const _: () = {
-    use futures::io::{AsyncReadExt, AsyncWriteExt};
+    use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
 
     impl<R> CrateAsyncRead for Compat<R>
     where
@@ -84,6 +89,19 @@ const _: () = {
         }
     }
 
+    impl<R> CrateAsyncBufRead for Compat<R>
+    where
+        R: futures::io::AsyncBufRead + Unpin,
+    {
+        fn fill_buf(&mut self) -> impl core::future::Future<Output = Result<&[u8], Self::Error>> {
+            AsyncBufReadExt::fill_buf(&mut self.0)
+        }
+
+        fn consume(&mut self, amt: usize) {
+            futures::io::AsyncBufRead::consume(Pin::new(&mut self.0), amt)
+        }
+    }
+
     impl<W> CrateAsyncWrite for Compat<W>
     where
         W: futures::io::AsyncWrite + Unpin,
@@ -100,5 +118,214 @@ const _: () = {
         fn flush(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
             self.0.flush()
         }
+
+        fn shutdown(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            self.0.close()
+        }
     }
 };
+
+pin_project_lite::pin_project! {
+    /// Pin-projected compatibility wrapper for `!Unpin` [`Futures-io' AsyncRead`](futures::io::AsyncRead) and [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
+    ///
+    /// - Converts a pinned `!Unpin` [`Futures-io' AsyncRead`](futures::io::AsyncRead) into a [`Crate's AsyncRead`](crate::io::AsyncRead).
+    /// - Converts a pinned `!Unpin` [`Futures-io' AsyncWrite`](futures::io::AsyncWrite) into a [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+    ///
+    /// The crate's traits are implemented for `Pin<&mut PinCompat<R>>`, so the wrapper must be pinned first,
+    /// e.g. using [`core::pin::pin!`] or `Box::pin`. For `Unpin` transports use [`Compat`].
+    #[derive(Debug)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct PinCompat<R> {
+        #[pin]
+        inner: R,
+    }
+}
+
+impl<R> PinCompat<R> {
+    /// Creates a new [`PinCompat`] from a [`Futures-io' AsyncRead`](futures::io::AsyncRead) or [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
+    #[inline]
+    pub const fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the inner [`Futures-io' AsyncRead`](futures::io::AsyncRead) or [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a pinned mutable reference to the inner [`Futures-io' AsyncRead`](futures::io::AsyncRead) or [`Futures-io' AsyncWrite`](futures::io::AsyncWrite).
+    #[inline]
+    pub fn inner_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Returns the inner [`Futures-io' AsyncRead`](futures::io::AsyncRead) or [`Futures-io' AsyncWrite`](futures::io::AsyncWrite) consuming this [`PinCompat`].
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> From<R> for PinCompat<R> {
+    fn from(inner: R) -> Self {
+        Self::new(inner)
+    }
+}
+
+const _: () = {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+    impl<R> CrateAsyncRead for Pin<&mut PinCompat<R>>
+    where
+        R: futures::io::AsyncRead,
+    {
+        type Error = futures::io::Error;
+
+        fn read<'a>(
+            &'a mut self,
+            buf: &'a mut [u8],
+        ) -> impl core::future::Future<Output = Result<usize, Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.read(buf).await }
+        }
+    }
+
+    impl<W> CrateAsyncWrite for Pin<&mut PinCompat<W>>
+    where
+        W: futures::io::AsyncWrite,
+    {
+        type Error = futures::io::Error;
+
+        fn write_all<'a>(
+            &'a mut self,
+            buf: &'a [u8],
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.write_all(buf).await }
+        }
+
+        fn flush(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.flush().await }
+        }
+
+        fn shutdown(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.close().await }
+        }
+    }
+};
+
+pub use crate::reverse_compat::{ReverseCompat, SendAsyncRead, SendAsyncWrite, SendReverseCompat};
+
+const _: () = {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+    impl<R> SendAsyncRead for Compat<R>
+    where
+        R: futures::io::AsyncRead + Unpin + Send,
+    {
+        fn read_send<'a>(
+            &'a mut self,
+            buf: &'a mut [u8],
+        ) -> impl core::future::Future<Output = Result<usize, Self::Error>> + Send + 'a {
+            self.0.read(buf)
+        }
+    }
+
+    impl<W> SendAsyncWrite for Compat<W>
+    where
+        W: futures::io::AsyncWrite + Unpin + Send,
+    {
+        fn write_all_send<'a>(
+            &'a mut self,
+            buf: &'a [u8],
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + 'a {
+            self.0.write_all(buf)
+        }
+
+        fn flush_send(
+            &mut self,
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + '_ {
+            self.0.flush()
+        }
+
+        fn shutdown_send(
+            &mut self,
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + '_ {
+            self.0.close()
+        }
+    }
+};
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+
+    use super::*;
+
+    const BYTES: &[u8] = b"Hello, world! How are you?";
+
+    #[tokio::test]
+    async fn reverse_compat_read() {
+        let mut read = ReverseCompat::new(Compat::new(Cursor::new(BYTES)));
+
+        let mut collected = Vec::new();
+
+        read.read_to_end(&mut collected).await.expect("Must read");
+
+        assert_eq!(BYTES, collected);
+    }
+
+    #[tokio::test]
+    async fn reverse_compat_write() {
+        let mut write = ReverseCompat::new(Compat::new(Vec::new()));
+
+        for chunk in BYTES.chunks(5) {
+            write.write_all(chunk).await.expect("Must write");
+        }
+
+        write.close().await.expect("Must close");
+
+        let collected = write.into_inner().expect("Must be idle").into_inner();
+
+        assert_eq!(BYTES, collected);
+    }
+
+    #[tokio::test]
+    async fn pin_compat() {
+        let mut read = core::pin::pin!(PinCompat::new(Cursor::new(BYTES)));
+        let mut write = core::pin::pin!(PinCompat::new(Cursor::new(Vec::new())));
+
+        let mut buf = [0_u8; 8];
+
+        loop {
+            let n = CrateAsyncRead::read(&mut read, &mut buf)
+                .await
+                .expect("Must read");
+
+            if n == 0 {
+                break;
+            }
+
+            CrateAsyncWrite::write_all(&mut write, &buf[..n])
+                .await
+                .expect("Must write");
+        }
+
+        CrateAsyncWrite::shutdown(&mut write)
+            .await
+            .expect("Must close");
+
+        assert_eq!(BYTES, write.inner().get_ref());
+    }
+}
@@ -37,16 +37,98 @@ pub trait AsyncWrite {
 
     /// Flush this output stream, ensuring that all intermediately buffered contents reach their destination.
     fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Shuts down the output stream, ensuring that the value can be dropped cleanly.
+    ///
+    /// The default implementation only flushes the output stream.
+    fn shutdown(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        self.flush()
+    }
 }
 
-impl AsyncWrite for &mut [u8] {
+/// An error returned when writing into a fixed size buffer that has no space left.
+///
+/// The bytes that fit into the buffer were written before the error was returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WriteZeroError;
+
+impl core::fmt::Display for WriteZeroError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Failed to write whole buffer")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WriteZeroError {}
+
+#[cfg(feature = "std")]
+impl From<WriteZeroError> for std::io::Error {
+    fn from(err: WriteZeroError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::WriteZero, err)
+    }
+}
+
+impl AsyncRead for &[u8] {
     type Error = core::convert::Infallible;
 
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        let amt = core::cmp::min(buf.len(), self.len());
+        let (a, b) = self.split_at(amt);
+        buf[..amt].copy_from_slice(a);
+        *self = b;
+        Ok(amt)
+    }
+}
+
+impl AsyncWrite for &mut [u8] {
+    type Error = WriteZeroError;
+
     async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<(), Self::Error> {
         let amt = core::cmp::min(buf.len(), self.len());
         let (a, b) = core::mem::take(self).split_at_mut(amt);
         a.copy_from_slice(&buf[..amt]);
         *self = b;
+
+        if amt < buf.len() {
+            return Err(WriteZeroError);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> AsyncWrite for heapless::Vec<u8, N> {
+    type Error = WriteZeroError;
+
+    async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<(), Self::Error> {
+        let amt = core::cmp::min(buf.len(), self.capacity() - self.len());
+
+        self.extend_from_slice(&buf[..amt])
+            .expect("The vector has enough capacity");
+
+        if amt < buf.len() {
+            return Err(WriteZeroError);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsyncWrite for std::vec::Vec<u8> {
+    type Error = core::convert::Infallible;
+
+    async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(buf);
         Ok(())
     }
 
@@ -54,3 +136,133 @@ impl AsyncWrite for &mut [u8] {
         Ok(())
     }
 }
+
+/// An asynchronous reader with an internal buffer.
+pub trait AsyncBufRead: AsyncRead {
+    /// Returns the contents of the internal buffer, filling it with more data from the underlying source if it is empty.
+    ///
+    /// An empty buffer is returned at EOF.
+    fn fill_buf(&mut self) -> impl Future<Output = Result<&[u8], Self::Error>>;
+
+    /// Tells this reader that `amt` bytes have been consumed from the buffer, so they should no longer be returned by [`AsyncBufRead::fill_buf`].
+    fn consume(&mut self, amt: usize);
+}
+
+impl<T: AsyncBufRead> AsyncBufRead for &mut T {
+    fn fill_buf(&mut self) -> impl Future<Output = Result<&[u8], Self::Error>> {
+        (*self).fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        (*self).consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use futures::StreamExt;
+
+    use crate::{
+        test::init_tracing, FramedRead, FramedReadError, FramedWrite, FramedWriteError, LinesCodec,
+        LinesCodecOwned,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn read_slice() {
+        init_tracing();
+
+        let capture: &[u8] = b"Hello\r\nHello, world!\nHei\r\nsu";
+
+        let mut framed_read =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), capture, [0_u8; 32]);
+
+        let collected = framed_read.stream().collect::<StdVec<_>>().await;
+
+        assert_eq!(4, collected.len());
+        assert!(matches!(collected[0], Ok(ref item) if item == b"Hello"));
+        assert!(matches!(collected[1], Ok(ref item) if item == b"Hello, world!"));
+        assert!(matches!(collected[2], Ok(ref item) if item == b"Hei"));
+        assert!(matches!(
+            collected[3],
+            Err(FramedReadError::BytesRemainingOnStream)
+        ));
+    }
+
+    #[tokio::test]
+    async fn write_slice() {
+        init_tracing();
+
+        let mut buf = [0_u8; 10];
+
+        let mut framed_write =
+            FramedWrite::new_with_buffer(LinesCodec::new(), &mut buf[..], [0_u8; 32]);
+
+        framed_write.send_frame(b"Hello").await.expect("Must send");
+
+        assert!(matches!(
+            framed_write.send_frame(b"Hey").await,
+            Err(FramedWriteError::IO(WriteZeroError))
+        ));
+
+        assert_eq!(0, framed_write.writer().len());
+        assert_eq!(b"Hello\r\nHey", &buf[..]);
+    }
+
+    #[tokio::test]
+    async fn write_heapless_vec() {
+        init_tracing();
+
+        let mut framed_write = FramedWrite::new_with_buffer(
+            LinesCodec::new(),
+            heapless::Vec::<u8, 10>::new(),
+            [0_u8; 32],
+        );
+
+        framed_write.send_frame(b"Hello").await.expect("Must send");
+
+        assert!(matches!(
+            framed_write.send_frame(b"Hey").await,
+            Err(FramedWriteError::IO(WriteZeroError))
+        ));
+
+        assert_eq!(b"Hello\r\nHey", framed_write.writer().as_slice());
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn write_vec() {
+        init_tracing();
+
+        let mut framed_write =
+            FramedWrite::new_with_buffer(LinesCodec::new(), StdVec::new(), [0_u8; 32]);
+
+        for item in [b"Hello".as_slice(), b"Hello, world!", b"Hei"] {
+            framed_write.send_frame(item).await.expect("Must send");
+        }
+
+        let (_, _, written) = framed_write.into_parts();
+
+        let mut framed_read = FramedRead::new_with_buffer(
+            LinesCodecOwned::<32>::new(),
+            written.as_slice(),
+            [0_u8; 32],
+        );
+
+        let collected = framed_read
+            .stream()
+            .collect::<StdVec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<StdVec<_>>();
+
+        assert_eq!(3, collected.len());
+        assert_eq!(b"Hello, world!", &collected[1][..]);
+    }
+}
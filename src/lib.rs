@@ -10,18 +10,26 @@
 
 pub mod codec;
 pub mod decode;
+pub mod duplex;
 pub mod encode;
+pub mod fault;
+pub mod framed_buf_read;
 pub mod framed_read;
 pub mod framed_write;
 pub mod io;
 pub(crate) mod logging;
+pub mod spsc;
 
 pub use codec::*;
 pub use decode::*;
+pub use duplex::*;
 pub use encode::*;
+pub use fault::*;
+pub use framed_buf_read::*;
 pub use framed_read::*;
 pub use framed_write::*;
 pub use io::*;
+pub use spsc::*;
 
 #[cfg(any(test, feature = "tokio"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
@@ -31,10 +39,25 @@ pub mod tokio;
 #[cfg_attr(docsrs, doc(cfg(feature = "futures-io")))]
 pub mod futures_io;
 
+#[cfg(feature = "asynchronous-codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "asynchronous-codec")))]
+pub mod asynchronous_codec;
+
+#[cfg(any(test, feature = "tokio", feature = "futures-io"))]
+mod reverse_compat;
+
 #[cfg(feature = "embedded-io-async")]
 #[cfg_attr(docsrs, doc(cfg(feature = "embedded-io-async")))]
 pub mod embedded_io_async;
 
+#[cfg(feature = "embassy")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embassy")))]
+pub mod embassy;
+
+#[cfg(any(test, feature = "testing"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 #[cfg(feature = "demo")]
 #[cfg_attr(docsrs, doc(cfg(feature = "demo")))]
 pub mod demo;
@@ -2,6 +2,7 @@
 
 #![allow(missing_docs)]
 
+#[cfg(any(feature = "log", feature = "defmt", feature = "tracing"))]
 pub mod formatter;
 
 #[macro_export]
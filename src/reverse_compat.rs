@@ -0,0 +1,530 @@
+//! Poll based driver on top of the crate's `async fn` based I/O traits.
+//!
+//! The futures returned by [`Crate's AsyncRead`](crate::io::AsyncRead) and [`Crate's AsyncWrite`](crate::io::AsyncWrite)
+//! borrow the reader or writer, so they can not be stored next to it between two `poll_*` calls.
+//! Instead, the inner value is moved into a boxed future that returns it back once the operation is completed.
+
+extern crate std;
+
+use std::{boxed::Box, future::Future, io, pin::Pin, task::Context, task::Poll, vec::Vec};
+
+use crate::io::{AsyncRead, AsyncWrite};
+
+/// An [`AsyncRead`] whose read futures are [`Send`], required by [`SendReverseCompat`].
+///
+/// The futures of [`AsyncRead`] can not be proven to be [`Send`] for a generic reader.
+/// Implement this trait for a concrete reader by forwarding to [`AsyncRead::read`], the compiler checks that the future is [`Send`].
+pub trait SendAsyncRead: AsyncRead + Send {
+    /// Reads bytes from the underlying source into the provided buffer returning how many bytes were read.
+    fn read_send<'a>(
+        &'a mut self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = Result<usize, Self::Error>> + Send + 'a;
+}
+
+/// An [`AsyncWrite`] whose write futures are [`Send`], required by [`SendReverseCompat`].
+///
+/// Implement this trait for a concrete writer by forwarding to the methods of [`AsyncWrite`].
+pub trait SendAsyncWrite: AsyncWrite + Send {
+    /// Writes all bytes from the provided buffer into the underlying sink.
+    fn write_all_send<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'a;
+
+    /// Flush this output stream, ensuring that all intermediately buffered contents reach their destination.
+    fn flush_send(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send + '_;
+
+    /// Shuts down the output stream, ensuring that the value can be dropped cleanly.
+    fn shutdown_send(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send + '_;
+}
+
+/// The operation that a [`ReverseCompatCore`] is currently driving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Read,
+    Write,
+    Flush,
+    Shutdown,
+}
+
+/// The inner value, the buffer of the operation, the operation and its result.
+type OpOutput<T> = (T, Vec<u8>, Op, io::Result<usize>);
+
+type LocalOp<T> = dyn Future<Output = OpOutput<T>>;
+
+type SendOp<T> = dyn Future<Output = OpOutput<T>> + Send;
+
+/// Creates the boxed future of a read operation.
+trait ReadOp<T>: Future<Output = OpOutput<T>> {
+    fn read(inner: T, buf: Vec<u8>) -> Pin<Box<Self>>;
+}
+
+/// Creates the boxed future of a write, flush or shutdown operation.
+trait WriteOp<T>: Future<Output = OpOutput<T>> {
+    fn write(inner: T, buf: Vec<u8>, op: Op) -> Pin<Box<Self>>;
+}
+
+impl<T> ReadOp<T> for LocalOp<T>
+where
+    T: AsyncRead + 'static,
+    T::Error: Into<io::Error>,
+{
+    fn read(mut inner: T, mut buf: Vec<u8>) -> Pin<Box<Self>> {
+        Box::pin(async move {
+            let result = inner.read(&mut buf).await.map_err(Into::into);
+
+            (inner, buf, Op::Read, result)
+        })
+    }
+}
+
+impl<T> ReadOp<T> for SendOp<T>
+where
+    T: SendAsyncRead + 'static,
+    T::Error: Into<io::Error>,
+{
+    fn read(mut inner: T, mut buf: Vec<u8>) -> Pin<Box<Self>> {
+        Box::pin(async move {
+            let result = inner.read_send(&mut buf).await.map_err(Into::into);
+
+            (inner, buf, Op::Read, result)
+        })
+    }
+}
+
+impl<T> WriteOp<T> for LocalOp<T>
+where
+    T: AsyncWrite + 'static,
+    T::Error: Into<io::Error>,
+{
+    fn write(mut inner: T, buf: Vec<u8>, op: Op) -> Pin<Box<Self>> {
+        Box::pin(async move {
+            let result = match op {
+                Op::Write => inner.write_all(&buf).await.map(|_| buf.len()),
+                Op::Flush => inner.flush().await.map(|_| 0),
+                _ => inner.shutdown().await.map(|_| 0),
+            };
+
+            (inner, buf, op, result.map_err(Into::into))
+        })
+    }
+}
+
+impl<T> WriteOp<T> for SendOp<T>
+where
+    T: SendAsyncWrite + 'static,
+    T::Error: Into<io::Error>,
+{
+    fn write(mut inner: T, buf: Vec<u8>, op: Op) -> Pin<Box<Self>> {
+        Box::pin(async move {
+            let result = match op {
+                Op::Write => inner.write_all_send(&buf).await.map(|_| buf.len()),
+                Op::Flush => inner.flush_send().await.map(|_| 0),
+                _ => inner.shutdown_send().await.map(|_| 0),
+            };
+
+            (inner, buf, op, result.map_err(Into::into))
+        })
+    }
+}
+
+enum State<T, F: ?Sized> {
+    /// No operation is in flight.
+    Idle(T),
+    /// An operation is in flight and owns the inner value.
+    Busy(Pin<Box<F>>),
+    /// The inner value was lost, because creating an operation panicked.
+    Poisoned,
+}
+
+/// Drives the crate's I/O traits from `poll_*` functions.
+///
+/// Operations are serialised: a pending read must complete before a write can start and vice versa.
+struct ReverseCompatCore<T, F: ?Sized> {
+    state: State<T, F>,
+    /// Bytes read from the inner reader that were not yet handed out.
+    read_buf: Vec<u8>,
+    /// The number of bytes of `read_buf` that were already handed out.
+    read_pos: usize,
+    /// The inner reader returned EOF.
+    read_eof: bool,
+    /// The inner reader returned an error that was not yet reported.
+    read_error: Option<io::Error>,
+    /// The result of a write that completed while another operation was polled.
+    write_result: Option<io::Result<usize>>,
+    /// The inner writer returned an error on flush or shutdown that was not yet reported.
+    write_error: Option<io::Error>,
+    /// A reusable buffer for the next operation.
+    scratch: Vec<u8>,
+}
+
+// The inner value is never pinned, it is moved into a boxed future.
+impl<T, F: ?Sized> Unpin for ReverseCompatCore<T, F> {}
+
+impl<T, F: ?Sized> core::fmt::Debug for ReverseCompatCore<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = match self.state {
+            State::Idle(_) => "Idle",
+            State::Busy(_) => "Busy",
+            State::Poisoned => "Poisoned",
+        };
+
+        f.debug_struct("ReverseCompatCore")
+            .field("state", &state)
+            .field("buffered", &(self.read_buf.len() - self.read_pos))
+            .field("read_eof", &self.read_eof)
+            .finish()
+    }
+}
+
+impl<T, F: ?Sized> ReverseCompatCore<T, F> {
+    const fn new(inner: T) -> Self {
+        Self {
+            state: State::Idle(inner),
+            read_buf: Vec::new(),
+            read_pos: 0,
+            read_eof: false,
+            read_error: None,
+            write_result: None,
+            write_error: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    fn inner(&self) -> Option<&T> {
+        match &self.state {
+            State::Idle(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    fn inner_mut(&mut self) -> Option<&mut T> {
+        match &mut self.state {
+            State::Idle(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    fn into_inner(self) -> Option<T> {
+        match self.state {
+            State::Idle(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Takes the inner value and a cleared scratch buffer out of an idle state.
+    ///
+    /// Must only be called after all in-flight operations are completed.
+    fn take(&mut self) -> io::Result<(T, Vec<u8>)> {
+        match core::mem::replace(&mut self.state, State::Poisoned) {
+            State::Idle(inner) => {
+                let mut buf = core::mem::take(&mut self.scratch);
+                buf.clear();
+
+                Ok((inner, buf))
+            }
+            State::Busy(_) => unreachable!("an operation is already in flight"),
+            State::Poisoned => Err(io::Error::other("inner I/O object lost")),
+        }
+    }
+}
+
+impl<T, F> ReverseCompatCore<T, F>
+where
+    F: Future<Output = OpOutput<T>> + ?Sized,
+{
+    /// Polls the in-flight operation, if any, to completion and stores its outcome.
+    ///
+    /// Returns which operation was completed.
+    fn poll_op(&mut self, cx: &mut Context<'_>) -> Poll<Option<Op>> {
+        let State::Busy(fut) = &mut self.state else {
+            return Poll::Ready(None);
+        };
+
+        let (inner, buf, op, result) = core::task::ready!(fut.as_mut().poll(cx));
+
+        self.state = State::Idle(inner);
+
+        match (op, result) {
+            (Op::Read, Ok(0)) => {
+                self.scratch = buf;
+                self.read_eof = true;
+            }
+            (Op::Read, Ok(n)) => {
+                self.scratch = core::mem::replace(&mut self.read_buf, buf);
+                self.read_buf.truncate(n);
+                self.read_pos = 0;
+            }
+            (Op::Read, Err(err)) => {
+                self.scratch = buf;
+                self.read_error = Some(err);
+            }
+            (Op::Write, result) => {
+                self.scratch = buf;
+                self.write_result = Some(result);
+            }
+            (_, result) => {
+                self.scratch = buf;
+
+                if let Err(err) = result {
+                    self.write_error = Some(err);
+                }
+            }
+        }
+
+        Poll::Ready(Some(op))
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, dst: &mut [u8]) -> Poll<io::Result<usize>>
+    where
+        F: ReadOp<T>,
+    {
+        loop {
+            if self.read_pos < self.read_buf.len() {
+                let n = core::cmp::min(dst.len(), self.read_buf.len() - self.read_pos);
+
+                dst[..n].copy_from_slice(&self.read_buf[self.read_pos..self.read_pos + n]);
+
+                self.read_pos += n;
+
+                return Poll::Ready(Ok(n));
+            }
+
+            if let Some(err) = self.read_error.take() {
+                return Poll::Ready(Err(err));
+            }
+
+            if core::mem::take(&mut self.read_eof) || dst.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            if core::task::ready!(self.poll_op(cx)).is_some() {
+                continue;
+            }
+
+            let (inner, mut buf) = self.take()?;
+            buf.resize(dst.len(), 0);
+
+            self.state = State::Busy(F::read(inner, buf));
+        }
+    }
+
+    /// Writes `src` and returns `Pending` until the write is completed.
+    ///
+    /// A pending write is completed before the next call is served, which returns the result of that write.
+    fn poll_write(&mut self, cx: &mut Context<'_>, src: &[u8]) -> Poll<io::Result<usize>>
+    where
+        F: WriteOp<T>,
+    {
+        loop {
+            if let Some(result) = self.write_result.take() {
+                return Poll::Ready(result);
+            }
+
+            if core::task::ready!(self.poll_op(cx)).is_some() {
+                continue;
+            }
+
+            if let Some(err) = self.write_error.take() {
+                return Poll::Ready(Err(err));
+            }
+
+            if src.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+
+            let (inner, mut buf) = self.take()?;
+            buf.extend_from_slice(src);
+
+            self.state = State::Busy(F::write(inner, buf, Op::Write));
+        }
+    }
+
+    /// Drives in-flight operations, then starts and drives `op` to completion.
+    fn poll_until(&mut self, cx: &mut Context<'_>, op: Op) -> Poll<io::Result<()>>
+    where
+        F: WriteOp<T>,
+    {
+        loop {
+            match core::task::ready!(self.poll_op(cx)) {
+                Some(completed) if completed == op => {
+                    return Poll::Ready(self.write_error.take().map_or(Ok(()), Err));
+                }
+                Some(_) => continue,
+                None => {
+                    // A write completed, but its caller never polled it again.
+                    // Its success is kept for the next `poll_write`, only its error is reported here.
+                    match self.write_result.take() {
+                        Some(Err(err)) => return Poll::Ready(Err(err)),
+                        result => self.write_result = result,
+                    }
+
+                    if let Some(err) = self.write_error.take() {
+                        return Poll::Ready(Err(err));
+                    }
+
+                    let (inner, buf) = self.take()?;
+
+                    self.state = State::Busy(F::write(inner, buf, op));
+                }
+            }
+        }
+    }
+}
+
+macro_rules! reverse_compat {
+    ($(#[$meta:meta])* $name:ident, $op:ident, $read:ident, $write:ident) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name<T>(ReverseCompatCore<T, $op<T>>);
+
+        impl<T> $name<T> {
+            #[doc = concat!("Creates a new [`", stringify!($name), "`] from a [`Crate's AsyncRead`](crate::io::AsyncRead) or [`Crate's AsyncWrite`](crate::io::AsyncWrite).")]
+            #[inline]
+            pub const fn new(inner: T) -> Self {
+                Self(ReverseCompatCore::new(inner))
+            }
+
+            /// Returns a reference to the inner [`Crate's AsyncRead`](crate::io::AsyncRead) or [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+            ///
+            /// Returns `None` if an operation is in flight.
+            #[inline]
+            pub fn inner(&self) -> Option<&T> {
+                self.0.inner()
+            }
+
+            /// Returns a mutable reference to the inner [`Crate's AsyncRead`](crate::io::AsyncRead) or [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+            ///
+            /// Returns `None` if an operation is in flight.
+            #[inline]
+            pub fn inner_mut(&mut self) -> Option<&mut T> {
+                self.0.inner_mut()
+            }
+
+            #[doc = concat!("Returns the inner [`Crate's AsyncRead`](crate::io::AsyncRead) or [`Crate's AsyncWrite`](crate::io::AsyncWrite) consuming this [`", stringify!($name), "`].")]
+            ///
+            /// Returns `None` if an operation is in flight. Bytes read but not yet consumed are lost.
+            #[inline]
+            pub fn into_inner(self) -> Option<T> {
+                self.0.into_inner()
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(inner: T) -> Self {
+                Self::new(inner)
+            }
+        }
+
+        #[cfg(any(test, feature = "tokio"))]
+        impl<R> tokio::io::AsyncRead for $name<R>
+        where
+            R: $read + 'static,
+            R::Error: Into<io::Error>,
+        {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut tokio::io::ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                let n = core::task::ready!(self.get_mut().0.poll_read(cx, buf.initialize_unfilled()))?;
+
+                buf.advance(n);
+
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        #[cfg(any(test, feature = "tokio"))]
+        impl<W> tokio::io::AsyncWrite for $name<W>
+        where
+            W: $write + 'static,
+            W::Error: Into<io::Error>,
+        {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().0.poll_write(cx, buf)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.get_mut().0.poll_until(cx, Op::Flush)
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.get_mut().0.poll_until(cx, Op::Shutdown)
+            }
+        }
+
+        #[cfg(feature = "futures-io")]
+        impl<R> futures::io::AsyncRead for $name<R>
+        where
+            R: $read + 'static,
+            R::Error: Into<io::Error>,
+        {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().0.poll_read(cx, buf)
+            }
+        }
+
+        #[cfg(feature = "futures-io")]
+        impl<W> futures::io::AsyncWrite for $name<W>
+        where
+            W: $write + 'static,
+            W::Error: Into<io::Error>,
+        {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().0.poll_write(cx, buf)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.get_mut().0.poll_until(cx, Op::Flush)
+            }
+
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.get_mut().0.poll_until(cx, Op::Shutdown)
+            }
+        }
+    };
+}
+
+reverse_compat!(
+    /// Reverse compatibility wrapper for [`Crate's AsyncRead`](crate::io::AsyncRead) and [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+    ///
+    /// - Converts a [`Crate's AsyncRead`](crate::io::AsyncRead) into a `tokio` or `futures-io` `AsyncRead`.
+    /// - Converts a [`Crate's AsyncWrite`](crate::io::AsyncWrite) into a `tokio` or `futures-io` `AsyncWrite`.
+    ///
+    /// Each operation moves the inner value into a boxed future until the operation is completed.
+    /// Therefore the I/O traits are only implemented for `'static` inner values and only one operation can be in flight at a time:
+    /// a pending read must complete before a write can start and vice versa.
+    ///
+    /// A write is pending until the inner writer completed it. The caller must poll the write again, as the I/O traits require.
+    ///
+    /// The futures of the crate's I/O traits are not known to be [`Send`], so this wrapper is not [`Send`].
+    /// Use [`SendReverseCompat`] to move the wrapper into a spawned task.
+    ReverseCompat,
+    LocalOp,
+    AsyncRead,
+    AsyncWrite
+);
+
+reverse_compat!(
+    /// A [`Send`] variant of [`ReverseCompat`] for readers implementing [`SendAsyncRead`] and writers implementing [`SendAsyncWrite`].
+    ///
+    /// [`SendReverseCompat`] is [`Send`] if the inner value is [`Send`], so it can be used in a spawned task.
+    SendReverseCompat,
+    SendOp,
+    SendAsyncRead,
+    SendAsyncWrite
+);
@@ -0,0 +1,338 @@
+//! Lock-free single-producer/single-consumer byte pipe.
+//!
+//! The [`SpscProducer`] never blocks and never takes a lock, so it can be fed from an interrupt handler,
+//! while the [`SpscConsumer`] implements [`AsyncRead`] and can be read by a [`FramedRead`](crate::FramedRead).
+
+use core::{
+    convert::Infallible,
+    sync::atomic::{AtomicBool, Ordering},
+    task::Poll,
+};
+
+use futures::task::AtomicWaker;
+use heapless::spsc::{Consumer, Producer, Queue};
+
+use crate::io::AsyncRead;
+
+/// A lock-free single-producer/single-consumer byte pipe backed by a [`heapless::spsc::Queue`].
+///
+/// The pipe can hold `N - 1` bytes and can be allocated statically, since [`SpscPipe::new`] is a `const fn`.
+///
+/// # Example
+///
+/// ```rust
+/// use cody_c::spsc::SpscPipe;
+///
+/// let mut pipe = SpscPipe::<64>::new();
+///
+/// let (mut producer, consumer) = pipe.split();
+///
+/// // Called from the UART RX interrupt.
+/// let pushed = producer.push(b"Hello\n");
+///
+/// assert_eq!(pushed, 6);
+/// ```
+#[derive(Debug)]
+pub struct SpscPipe<const N: usize> {
+    queue: Queue<u8, N>,
+    /// Waker of the task waiting for bytes on the [`SpscConsumer`].
+    waker: AtomicWaker,
+    /// The [`SpscProducer`] was closed or dropped.
+    closed: AtomicBool,
+}
+
+impl<const N: usize> Default for SpscPipe<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SpscPipe<N> {
+    /// Creates a new empty [`SpscPipe`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            queue: Queue::new(),
+            waker: AtomicWaker::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the maximum number of bytes the pipe can hold.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N - 1
+    }
+
+    /// Splits the pipe into a [`SpscProducer`] and a [`SpscConsumer`].
+    pub fn split(&mut self) -> (SpscProducer<'_, N>, SpscConsumer<'_, N>) {
+        let Self {
+            queue,
+            waker,
+            closed,
+        } = self;
+
+        closed.store(false, Ordering::Release);
+
+        let (producer, consumer) = queue.split();
+
+        (
+            SpscProducer {
+                producer,
+                waker,
+                closed,
+            },
+            SpscConsumer {
+                consumer,
+                waker,
+                closed,
+            },
+        )
+    }
+}
+
+/// The producing half of a [`SpscPipe`].
+///
+/// Dropping the producer closes the pipe. The [`SpscConsumer`] then reads the remaining bytes followed by EOF.
+pub struct SpscProducer<'a, const N: usize> {
+    producer: Producer<'a, u8, N>,
+    waker: &'a AtomicWaker,
+    closed: &'a AtomicBool,
+}
+
+impl<const N: usize> core::fmt::Debug for SpscProducer<'_, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpscProducer")
+            .field("len", &self.producer.len())
+            .field("capacity", &self.producer.capacity())
+            .finish()
+    }
+}
+
+impl<const N: usize> SpscProducer<'_, N> {
+    /// Pushes as many bytes of `bytes` as fit into the pipe and returns how many bytes were pushed.
+    ///
+    /// Never blocks. Bytes that do not fit are not pushed.
+    pub fn push(&mut self, bytes: &[u8]) -> usize {
+        let mut pushed = 0;
+
+        for byte in bytes {
+            if self.producer.enqueue(*byte).is_err() {
+                break;
+            }
+
+            pushed += 1;
+        }
+
+        if pushed > 0 {
+            self.waker.wake();
+        }
+
+        pushed
+    }
+
+    /// Returns `true` if the pipe can not hold any more bytes.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        !self.producer.ready()
+    }
+
+    /// Returns the number of bytes in the pipe.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.producer.len()
+    }
+
+    /// Returns `true` if the pipe is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.producer.len() == 0
+    }
+
+    /// Closes the pipe. Same as dropping the producer.
+    #[inline]
+    pub fn close(self) {}
+}
+
+impl<const N: usize> Drop for SpscProducer<'_, N> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.waker.wake();
+    }
+}
+
+/// The consuming half of a [`SpscPipe`].
+///
+/// Reading waits until at least one byte is available and returns `0` (EOF) once the [`SpscProducer`] is closed and the pipe is drained.
+pub struct SpscConsumer<'a, const N: usize> {
+    consumer: Consumer<'a, u8, N>,
+    waker: &'a AtomicWaker,
+    closed: &'a AtomicBool,
+}
+
+impl<const N: usize> core::fmt::Debug for SpscConsumer<'_, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpscConsumer")
+            .field("len", &self.consumer.len())
+            .field("capacity", &self.consumer.capacity())
+            .field("closed", &self.closed.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+impl<const N: usize> SpscConsumer<'_, N> {
+    /// Pops as many bytes as available into `buf` and returns how many bytes were popped.
+    ///
+    /// Never blocks.
+    pub fn pop(&mut self, buf: &mut [u8]) -> usize {
+        let mut popped = 0;
+
+        for slot in buf.iter_mut() {
+            match self.consumer.dequeue() {
+                Some(byte) => *slot = byte,
+                None => break,
+            }
+
+            popped += 1;
+        }
+
+        popped
+    }
+
+    /// Returns the number of bytes in the pipe.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.consumer.len()
+    }
+
+    /// Returns `true` if the pipe is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.consumer.ready()
+    }
+
+    /// Returns `true` if the [`SpscProducer`] was closed or dropped.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+}
+
+impl<const N: usize> AsyncRead for SpscConsumer<'_, N> {
+    type Error = Infallible;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        futures::future::poll_fn(|cx| {
+            // Load before popping: bytes pushed before the close are not lost.
+            let closed = self.closed.load(Ordering::Acquire);
+
+            let popped = self.pop(buf);
+
+            if popped > 0 {
+                return Poll::Ready(Ok(popped));
+            }
+
+            if closed {
+                return Poll::Ready(Ok(0));
+            }
+
+            self.waker.register(cx.waker());
+
+            // Bytes might have been pushed or the pipe closed before the waker was registered.
+            if self.consumer.ready() || self.closed.load(Ordering::Acquire) {
+                cx.waker().wake_by_ref();
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec as StdVec;
+
+    use futures::StreamExt;
+
+    use crate::{test::init_tracing, FramedRead, LinesCodecOwned};
+
+    use super::*;
+
+    #[test]
+    fn push_until_full() {
+        let mut pipe = SpscPipe::<8>::new();
+
+        let (mut producer, mut consumer) = pipe.split();
+
+        assert_eq!(7, producer.push(b"Hello, world!"));
+        assert!(producer.is_full());
+        assert_eq!(0, producer.push(b"!"));
+
+        let mut buf = [0_u8; 4];
+
+        assert_eq!(4, consumer.pop(&mut buf));
+        assert_eq!(b"Hell", &buf);
+        assert_eq!(4, producer.push(b"Hey!"));
+        assert_eq!(7, consumer.len());
+    }
+
+    #[test]
+    fn framed_read_from_thread() {
+        init_tracing();
+
+        let items: StdVec<heapless::Vec<u8, 32>> = (0..100)
+            .map(|i| {
+                let mut item = heapless::Vec::new();
+                core::fmt::Write::write_fmt(&mut item, format_args!("Item {i}")).unwrap();
+                item
+            })
+            .collect();
+
+        let mut pipe = SpscPipe::<16>::new();
+
+        let (mut producer, consumer) = pipe.split();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Must build runtime");
+
+        let collected = std::thread::scope(|scope| {
+            let items = &items;
+
+            // Simulates an interrupt handler pushing bytes as they arrive.
+            scope.spawn(move || {
+                for item in items {
+                    for byte in item.as_slice().iter().chain(b"\n") {
+                        while producer.push(core::slice::from_ref(byte)) == 0 {
+                            std::thread::yield_now();
+                        }
+                    }
+                }
+
+                producer.close();
+            });
+
+            runtime.block_on(async {
+                let mut framed_read =
+                    FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), consumer, [0_u8; 64]);
+
+                framed_read
+                    .stream()
+                    .collect::<StdVec<_>>()
+                    .await
+                    .into_iter()
+                    .flatten()
+                    .collect::<StdVec<_>>()
+            })
+        });
+
+        assert_eq!(items, collected);
+    }
+}
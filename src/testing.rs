@@ -0,0 +1,884 @@
+//! Helpers for testing codecs.
+//!
+//! - [`ChunkedReader`] feeds an input to a [`FramedRead`] in chunks of a fixed size, or split at given offsets.
+//! - [`decode_chunked`] and [`decode_chunked_owned`] decode an input read in chunks of a fixed size.
+//!   [`decode_split`] and [`decode_split_owned`] decode an input split at given offsets.
+//! - [`assert_decode_chunked`] and [`assert_decode_chunked_owned`] assert that a decoder yields the same frames for every chunk size.
+//!   [`assert_decode_splits`] and [`assert_decode_splits_owned`] assert the same for irregular reads, split at every one or two offsets.
+//! - [`assert_roundtrip`] asserts that items encoded by an [`Encoder`] are decoded back by a [`DecoderOwned`].
+//! - [`assert_decoder_conformance`] and [`assert_codec_conformance`] run a codec against the invariants every codec must hold.
+//!   [`conformance_items`] returns the items shared by the conformance tests, [`assert_codec_conformance_items`] runs a codec on them and
+//!   [`assert_decoder_conformance_truncated`] checks a decoder on an input ending with a truncated frame.
+//! - [`to_vecs`] converts byte strings into the frames collected from a borrowed decoder.
+//!
+//! The helpers are `async` but never wait, so they can be driven by any executor.
+//!
+//! # Example
+//!
+//! ```rust
+//! use cody_c::{testing::assert_decode_chunked, FramedReadError, LinesCodec};
+//!
+//! # async fn run() {
+//! let expected = [b"Hello".to_vec(), b"Hello, world!".to_vec()];
+//!
+//! let error =
+//!     assert_decode_chunked::<32, _, _, _>(LinesCodec::new(), b"Hello\nHello, world!\r\n", &expected)
+//!         .await;
+//!
+//! assert!(matches!(error, FramedReadError::EOF));
+//! # }
+//! ```
+
+extern crate std;
+
+use core::{
+    convert::Infallible,
+    fmt::Debug,
+    future::Future,
+    mem::discriminant,
+    task::{Context, Poll, Waker},
+};
+use std::{format, string::String, vec::Vec};
+
+use crate::{
+    decode::{Decoder, DecoderOwned},
+    encode::{Encoder, EncoderEof},
+    framed_read::{FramedRead, FramedReadError},
+    io::AsyncRead,
+};
+
+/// A reader returning at most `chunk_size` bytes of its input per read, or reads ending at given split offsets.
+///
+/// Reading returns `0` (EOF) once the input is exhausted.
+#[derive(Debug, Clone)]
+pub struct ChunkedReader<'a> {
+    input: &'a [u8],
+    chunk_size: usize,
+    /// The offsets into the original input where reads end, in increasing order.
+    splits: &'a [usize],
+    /// The number of bytes read so far.
+    position: usize,
+}
+
+impl<'a> ChunkedReader<'a> {
+    /// Creates a new [`ChunkedReader`] with the given `input` and `chunk_size`.
+    ///
+    /// A `chunk_size` of `0` is treated as `1`.
+    #[inline]
+    pub const fn new(input: &'a [u8], chunk_size: usize) -> Self {
+        Self {
+            input,
+            chunk_size: if chunk_size == 0 { 1 } else { chunk_size },
+            splits: &[],
+            position: 0,
+        }
+    }
+
+    /// Creates a new [`ChunkedReader`] whose reads end at the given `splits` offsets into `input`.
+    ///
+    /// E.g. splits `[1, 7]` read the bytes `0..1`, `1..7` and the rest of `input`, unless the destination is smaller.
+    /// Offsets that are not increasing or exceed the length of `input` are ignored.
+    #[inline]
+    pub const fn with_splits(input: &'a [u8], splits: &'a [usize]) -> Self {
+        Self {
+            input,
+            chunk_size: usize::MAX,
+            splits,
+            position: 0,
+        }
+    }
+
+    /// Returns the bytes that were not read yet.
+    #[inline]
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.input
+    }
+
+    /// Returns the maximum number of bytes returned by a single read.
+    #[inline]
+    pub const fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the offsets into the input where reads end.
+    #[inline]
+    pub const fn splits(&self) -> &'a [usize] {
+        self.splits
+    }
+}
+
+impl AsyncRead for ChunkedReader<'_> {
+    type Error = Infallible;
+
+    async fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize, Self::Error> {
+        let until_split = self
+            .splits
+            .iter()
+            .find(|split| **split > self.position)
+            .map_or(usize::MAX, |split| split - self.position);
+
+        let amt = buf
+            .len()
+            .min(self.chunk_size)
+            .min(until_split)
+            .min(self.input.len());
+        let (chunk, rest) = self.input.split_at(amt);
+
+        buf[..amt].copy_from_slice(chunk);
+        self.input = rest;
+        self.position += amt;
+
+        Ok(amt)
+    }
+}
+
+/// The frames decoded from an input and the error that stopped decoding.
+///
+/// A fully decoded input stops with [`FramedReadError::EOF`].
+#[derive(Debug)]
+pub struct Decoded<T, E> {
+    /// The decoded frames.
+    pub frames: Vec<T>,
+    /// The error that stopped decoding.
+    pub error: FramedReadError<Infallible, E>,
+}
+
+/// Converts byte strings into owned frames, e.g. the `expected` frames of [`assert_decode_chunked`] for a decoder yielding `&[u8]`.
+pub fn to_vecs(items: &[&[u8]]) -> Vec<Vec<u8>> {
+    items.iter().map(|item| item.to_vec()).collect()
+}
+
+/// Decodes the input of `reader` using a [`FramedRead`] with a buffer of `N` bytes.
+async fn decode_reader<const N: usize, D, T, E>(
+    decoder: D,
+    reader: ChunkedReader<'_>,
+) -> Decoded<T, E>
+where
+    D: for<'a> Decoder<'a, Error = E>,
+    for<'a> <D as Decoder<'a>>::Item: Into<T>,
+{
+    let mut framed_read = FramedRead::new_with_buffer(decoder, reader, [0_u8; N]);
+
+    let mut frames = Vec::new();
+
+    loop {
+        match framed_read.read_frame().await {
+            Ok(Some(item)) => frames.push(item.into()),
+            Ok(None) => {}
+            Err(error) => return Decoded { frames, error },
+        }
+    }
+}
+
+/// Decodes the input of `reader` using a [`FramedRead`] with a buffer of `N` bytes.
+async fn decode_reader_owned<const N: usize, D>(
+    decoder: D,
+    reader: ChunkedReader<'_>,
+) -> Decoded<D::Item, D::Error>
+where
+    D: DecoderOwned,
+{
+    let mut framed_read = FramedRead::new_with_buffer(decoder, reader, [0_u8; N]);
+
+    let mut frames = Vec::new();
+
+    loop {
+        match framed_read.read_frame_owned().await {
+            Ok(item) => frames.push(item),
+            Err(error) => return Decoded { frames, error },
+        }
+    }
+}
+
+/// Decodes `input` read in chunks of `chunk_size` bytes using a [`FramedRead`] with a buffer of `N` bytes.
+///
+/// Uses [`FramedRead::read_frame`] and converts every borrowed frame into `T`.
+pub async fn decode_chunked<const N: usize, D, T, E>(
+    decoder: D,
+    input: &[u8],
+    chunk_size: usize,
+) -> Decoded<T, E>
+where
+    D: for<'a> Decoder<'a, Error = E>,
+    for<'a> <D as Decoder<'a>>::Item: Into<T>,
+{
+    decode_reader::<N, _, _, _>(decoder, ChunkedReader::new(input, chunk_size)).await
+}
+
+/// Decodes `input` read in chunks of `chunk_size` bytes using a [`FramedRead`] with a buffer of `N` bytes.
+///
+/// Uses [`FramedRead::read_frame_owned`].
+pub async fn decode_chunked_owned<const N: usize, D>(
+    decoder: D,
+    input: &[u8],
+    chunk_size: usize,
+) -> Decoded<D::Item, D::Error>
+where
+    D: DecoderOwned,
+{
+    decode_reader_owned::<N, _>(decoder, ChunkedReader::new(input, chunk_size)).await
+}
+
+/// Decodes `input` split at the `splits` offsets using a [`FramedRead`] with a buffer of `N` bytes.
+///
+/// Uses [`FramedRead::read_frame`] and converts every borrowed frame into `T`. See [`ChunkedReader::with_splits`].
+pub async fn decode_split<const N: usize, D, T, E>(
+    decoder: D,
+    input: &[u8],
+    splits: &[usize],
+) -> Decoded<T, E>
+where
+    D: for<'a> Decoder<'a, Error = E>,
+    for<'a> <D as Decoder<'a>>::Item: Into<T>,
+{
+    decode_reader::<N, _, _, _>(decoder, ChunkedReader::with_splits(input, splits)).await
+}
+
+/// Decodes `input` split at the `splits` offsets using a [`FramedRead`] with a buffer of `N` bytes.
+///
+/// Uses [`FramedRead::read_frame_owned`]. See [`ChunkedReader::with_splits`].
+pub async fn decode_split_owned<const N: usize, D>(
+    decoder: D,
+    input: &[u8],
+    splits: &[usize],
+) -> Decoded<D::Item, D::Error>
+where
+    D: DecoderOwned,
+{
+    decode_reader_owned::<N, _>(decoder, ChunkedReader::with_splits(input, splits)).await
+}
+
+/// Returns every way to split an input of `len` bytes at one or two offsets, e.g. `[1, 7]`, or `[1, 1]` for a single split.
+fn split_points(len: usize) -> impl Iterator<Item = [usize; 2]> {
+    (1..len).flat_map(move |first| (first..len).map(move |second| [first, second]))
+}
+
+/// Asserts that every way of reading, e.g. every chunk size, yields the `expected` frames and the same kind of error.
+fn assert_same_outcome<T, E>(
+    outcomes: impl IntoIterator<Item = (String, Decoded<T, E>)>,
+    expected: &[T],
+) -> FramedReadError<Infallible, E>
+where
+    T: PartialEq + Debug,
+{
+    let mut last: Option<FramedReadError<Infallible, E>> = None;
+
+    for (reads, decoded) in outcomes {
+        assert_eq!(
+            expected,
+            decoded.frames.as_slice(),
+            "Unexpected frames with {reads}"
+        );
+
+        if let Some(last) = &last {
+            assert_eq!(
+                discriminant(last),
+                discriminant(&decoded.error),
+                "Unexpected error with {reads}"
+            );
+        }
+
+        last = Some(decoded.error);
+    }
+
+    last.expect("The input is read at least once")
+}
+
+/// Asserts that `decoder` yields the `expected` frames from `input` for every chunk size from `1` to `input.len()`, using [`decode_chunked`].
+///
+/// Also asserts that decoding stops with the same kind of error for every chunk size and returns that error,
+/// e.g. [`FramedReadError::EOF`] for a fully decoded input or [`FramedReadError::BufferTooSmall`] if `N` is too small.
+///
+/// # Panics
+///
+/// Panics if an assertion fails.
+pub async fn assert_decode_chunked<const N: usize, D, T, E>(
+    decoder: D,
+    input: &[u8],
+    expected: &[T],
+) -> FramedReadError<Infallible, E>
+where
+    D: for<'a> Decoder<'a, Error = E> + Clone,
+    for<'a> <D as Decoder<'a>>::Item: Into<T>,
+    T: PartialEq + Debug,
+{
+    let mut outcomes = Vec::new();
+
+    for chunk_size in 1..=input.len().max(1) {
+        outcomes.push((
+            format!("chunk size {chunk_size}"),
+            decode_chunked::<N, _, _, _>(decoder.clone(), input, chunk_size).await,
+        ));
+    }
+
+    assert_same_outcome(outcomes, expected)
+}
+
+/// Asserts that `decoder` yields the `expected` frames from `input` for every chunk size from `1` to `input.len()`, using [`decode_chunked_owned`].
+///
+/// See [`assert_decode_chunked`].
+///
+/// # Panics
+///
+/// Panics if an assertion fails.
+pub async fn assert_decode_chunked_owned<const N: usize, D>(
+    decoder: D,
+    input: &[u8],
+    expected: &[D::Item],
+) -> FramedReadError<Infallible, D::Error>
+where
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq + Debug,
+{
+    let mut outcomes = Vec::new();
+
+    for chunk_size in 1..=input.len().max(1) {
+        outcomes.push((
+            format!("chunk size {chunk_size}"),
+            decode_chunked_owned::<N, _>(decoder.clone(), input, chunk_size).await,
+        ));
+    }
+
+    assert_same_outcome(outcomes, expected)
+}
+
+/// Asserts that `decoder` yields the `expected` frames from `input` split at every one or two offsets, using [`decode_split`].
+///
+/// Unlike [`assert_decode_chunked`], the reads have irregular sizes, e.g. `1`, `6` and the rest of `input` for the splits `[1, 7]`.
+/// Also asserts that decoding stops with the same kind of error for every split and returns that error.
+///
+/// Decodes `input` about `input.len()² / 2` times, so keep inputs short.
+///
+/// # Panics
+///
+/// Panics if an assertion fails.
+pub async fn assert_decode_splits<const N: usize, D, T, E>(
+    decoder: D,
+    input: &[u8],
+    expected: &[T],
+) -> FramedReadError<Infallible, E>
+where
+    D: for<'a> Decoder<'a, Error = E> + Clone,
+    for<'a> <D as Decoder<'a>>::Item: Into<T>,
+    T: PartialEq + Debug,
+{
+    let mut outcomes = std::vec![(
+        String::from("no splits"),
+        decode_split::<N, _, _, _>(decoder.clone(), input, &[]).await,
+    )];
+
+    for splits in split_points(input.len()) {
+        outcomes.push((
+            format!("splits {splits:?}"),
+            decode_split::<N, _, _, _>(decoder.clone(), input, &splits).await,
+        ));
+    }
+
+    assert_same_outcome(outcomes, expected)
+}
+
+/// Asserts that `decoder` yields the `expected` frames from `input` split at every one or two offsets, using [`decode_split_owned`].
+///
+/// See [`assert_decode_splits`].
+///
+/// # Panics
+///
+/// Panics if an assertion fails.
+pub async fn assert_decode_splits_owned<const N: usize, D>(
+    decoder: D,
+    input: &[u8],
+    expected: &[D::Item],
+) -> FramedReadError<Infallible, D::Error>
+where
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq + Debug,
+{
+    let mut outcomes = std::vec![(
+        String::from("no splits"),
+        decode_split_owned::<N, _>(decoder.clone(), input, &[]).await,
+    )];
+
+    for splits in split_points(input.len()) {
+        outcomes.push((
+            format!("splits {splits:?}"),
+            decode_split_owned::<N, _>(decoder.clone(), input, &splits).await,
+        ));
+    }
+
+    assert_same_outcome(outcomes, expected)
+}
+
+/// Asserts that `items` encoded by `encoder`, followed by its closing frame, are decoded back by `decoder` for every chunk size, using buffers of `N` bytes.
+///
+/// Returns the encoded bytes.
+///
+/// # Panics
+///
+/// Panics if encoding fails, if an item is not decoded back or if decoding does not stop with [`FramedReadError::EOF`].
+pub async fn assert_roundtrip<const N: usize, E, D, I>(
+    mut encoder: E,
+    decoder: D,
+    items: &[I],
+) -> Vec<u8>
+where
+    E: Encoder<I> + EncoderEof<Error = <E as Encoder<I>>::Error>,
+    <E as Encoder<I>>::Error: Debug,
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq<I> + Debug,
+    D::Error: Debug,
+    I: Clone + Debug,
+{
+    let mut buf = [0_u8; N];
+    let mut encoded = Vec::new();
+
+    for item in items {
+        let size = encoder.encode(item.clone(), &mut buf).expect("Must encode");
+
+        encoded.extend_from_slice(&buf[..size]);
+    }
+
+    let size = encoder.encode_eof(&mut buf).expect("Must encode");
+
+    encoded.extend_from_slice(&buf[..size]);
+
+    for chunk_size in 1..=encoded.len().max(1) {
+        let decoded = decode_chunked_owned::<N, _>(decoder.clone(), &encoded, chunk_size).await;
+
+        assert_eq!(
+            decoded.frames.as_slice(),
+            items,
+            "Unexpected frames with chunk size {chunk_size}"
+        );
+
+        assert!(
+            matches!(decoded.error, FramedReadError::EOF),
+            "Unexpected error with chunk size {chunk_size}: {:?}",
+            decoded.error
+        );
+    }
+
+    encoded
+}
+
+/// A decoder checking every consumed size of the wrapped decoder.
+#[derive(Debug)]
+struct CheckedDecoder<D> {
+    inner: D,
+    /// The consumed size of every decoded frame and whether it was decoded by [`DecoderOwned::decode_eof_owned`].
+    sizes: Vec<(usize, bool)>,
+    chunk_size: usize,
+    buffer_size: usize,
+}
+
+impl<D: DecoderOwned> CheckedDecoder<D> {
+    fn check(
+        &mut self,
+        result: Result<Option<(D::Item, usize)>, D::Error>,
+        len: usize,
+        eof: bool,
+    ) -> Result<Option<(D::Item, usize)>, D::Error> {
+        if let Ok(Some((_, size))) = &result {
+            assert_consumed(*size, len, || {
+                format!(
+                    "chunk size {}, buffer size {}, eof {eof}",
+                    self.chunk_size, self.buffer_size
+                )
+            });
+
+            self.sizes.push((*size, eof));
+        }
+
+        result
+    }
+}
+
+impl<D: DecoderOwned> DecoderOwned for CheckedDecoder<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        let result = self.inner.decode_owned(src);
+
+        self.check(result, src.len(), false)
+    }
+
+    fn decode_eof_owned(
+        &mut self,
+        src: &mut [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        let result = self.inner.decode_eof_owned(src);
+
+        self.check(result, src.len(), true)
+    }
+}
+
+/// The outcome of decoding with a [`CheckedDecoder`].
+struct Checked<T, E> {
+    frames: Vec<T>,
+    /// The consumed size of every frame and whether it was decoded on EOF.
+    sizes: Vec<(usize, bool)>,
+    error: FramedReadError<Infallible, E>,
+    /// The number of bytes in the buffer when decoding stopped.
+    remaining: usize,
+}
+
+/// The buffer sizes of the [`FramedRead`]s used by [`assert_decoder_conformance`].
+const CONFORMANCE_BUFFER_SIZES: &[usize] = &[
+    1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1024,
+];
+
+/// Decodes `input` read in chunks of `chunk_size` bytes using a [`FramedRead`] with a buffer of `N` bytes and a [`CheckedDecoder`].
+async fn decode_checked<const N: usize, D>(
+    decoder: D,
+    input: &[u8],
+    chunk_size: usize,
+) -> Checked<D::Item, D::Error>
+where
+    D: DecoderOwned,
+{
+    let decoder = CheckedDecoder {
+        inner: decoder,
+        sizes: Vec::new(),
+        chunk_size,
+        buffer_size: N,
+    };
+
+    let mut framed_read =
+        FramedRead::new_with_buffer(decoder, ChunkedReader::new(input, chunk_size), [0_u8; N]);
+
+    let mut frames = Vec::new();
+
+    loop {
+        match framed_read.read_frame_owned().await {
+            Ok(item) => frames.push(item),
+            Err(error) => {
+                let remaining = framed_read.state().framable();
+                let (_, decoder, _) = framed_read.into_parts();
+
+                return Checked {
+                    frames,
+                    sizes: decoder.sizes,
+                    error,
+                    remaining,
+                };
+            }
+        }
+    }
+}
+
+/// Runs [`decode_checked`] with a buffer of `buffer_size` bytes, one of the [`CONFORMANCE_BUFFER_SIZES`].
+fn decode_checked_sized<D>(
+    decoder: D,
+    input: &[u8],
+    chunk_size: usize,
+    buffer_size: usize,
+) -> Checked<D::Item, D::Error>
+where
+    D: DecoderOwned,
+{
+    let future = async {
+        match buffer_size {
+            1 => decode_checked::<1, _>(decoder, input, chunk_size).await,
+            2 => decode_checked::<2, _>(decoder, input, chunk_size).await,
+            3 => decode_checked::<3, _>(decoder, input, chunk_size).await,
+            4 => decode_checked::<4, _>(decoder, input, chunk_size).await,
+            6 => decode_checked::<6, _>(decoder, input, chunk_size).await,
+            8 => decode_checked::<8, _>(decoder, input, chunk_size).await,
+            12 => decode_checked::<12, _>(decoder, input, chunk_size).await,
+            16 => decode_checked::<16, _>(decoder, input, chunk_size).await,
+            24 => decode_checked::<24, _>(decoder, input, chunk_size).await,
+            32 => decode_checked::<32, _>(decoder, input, chunk_size).await,
+            48 => decode_checked::<48, _>(decoder, input, chunk_size).await,
+            64 => decode_checked::<64, _>(decoder, input, chunk_size).await,
+            96 => decode_checked::<96, _>(decoder, input, chunk_size).await,
+            128 => decode_checked::<128, _>(decoder, input, chunk_size).await,
+            192 => decode_checked::<192, _>(decoder, input, chunk_size).await,
+            256 => decode_checked::<256, _>(decoder, input, chunk_size).await,
+            384 => decode_checked::<384, _>(decoder, input, chunk_size).await,
+            512 => decode_checked::<512, _>(decoder, input, chunk_size).await,
+            768 => decode_checked::<768, _>(decoder, input, chunk_size).await,
+            1024 => decode_checked::<1024, _>(decoder, input, chunk_size).await,
+            _ => unreachable!("Unsupported buffer size {buffer_size}"),
+        }
+    };
+
+    // A `ChunkedReader` never waits, so the future completes on the first poll.
+    match core::pin::pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(checked) => checked,
+        Poll::Pending => unreachable!("Decoding from a ChunkedReader never waits"),
+    }
+}
+
+/// Asserts that a consumed `size` is valid for a source of `len` bytes.
+fn assert_consumed(size: usize, len: usize, context: impl Fn() -> String) {
+    assert!(
+        size <= len,
+        "Consumed {size} bytes from a source of {len} bytes ({})",
+        context()
+    );
+
+    assert!(
+        size > 0,
+        "Decoded a frame without consuming bytes, a FramedRead would yield it forever ({})",
+        context()
+    );
+}
+
+/// Asserts that `frames` equal `expected`.
+fn assert_frames<T, U>(frames: &[T], expected: &[U], context: impl Fn() -> String)
+where
+    T: PartialEq<U> + Debug,
+    U: Debug,
+{
+    assert!(
+        frames.len() == expected.len()
+            && frames
+                .iter()
+                .zip(expected)
+                .all(|(frame, expected)| frame == expected),
+        "Unexpected frames with {}: {frames:?}, expected: {expected:?}",
+        context()
+    );
+}
+
+/// Asserts that `decoder` holds the invariants every decoder must hold while decoding `input`, and that it yields the `expected` frames.
+///
+/// The invariants are:
+/// - The consumed size of a frame is at least `1` and never exceeds the length of the source,
+///   both for [`DecoderOwned::decode_owned`] and [`DecoderOwned::decode_eof_owned`], for every prefix of `input`
+///   and for every call of a [`FramedRead`].
+/// - Every chunk size from `1` to `input.len()` and every buffer size large enough to hold the largest frame
+///   yields the same frames and stops with the same kind of [`FramedReadError`].
+///
+/// Decoding runs a [`FramedRead`] for buffer sizes from `1` to `1024` bytes, roughly doubling, up to the first one holding the whole `input`.
+/// Every buffer size is combined with every chunk size, so the cost grows with the square of `input.len()`:
+/// keep inputs to a few hundred bytes, and below `1024` bytes.
+/// Run the tests with and without the `buffer-early-shift` feature, so decoders keeping state across calls
+/// are checked not to depend on the position of the source in the buffer.
+/// Decoders that frame whatever bytes are available, like [`BytesCodecOwned`](crate::BytesCodecOwned), do not yield the same frames for every chunk size and can not pass.
+///
+/// Returns the error that stopped decoding, e.g. [`FramedReadError::EOF`] for a fully decoded input.
+///
+/// # Panics
+///
+/// Panics if an invariant is violated, if the frames differ from `expected` or if `input` is not shorter than `1024` bytes.
+pub fn assert_decoder_conformance<D, T>(
+    decoder: D,
+    input: &[u8],
+    expected: &[T],
+) -> FramedReadError<Infallible, D::Error>
+where
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq<T> + Debug,
+    D::Error: Debug,
+    T: Debug,
+{
+    for len in 0..=input.len() {
+        let context = || format!("prefix of {len} bytes");
+
+        let mut src = input[..len].to_vec();
+
+        if let Ok(Some((_, size))) = decoder.clone().decode_owned(&mut src) {
+            assert_consumed(size, len, context);
+        }
+
+        let mut src = input[..len].to_vec();
+
+        if let Ok(Some((_, size))) = decoder.clone().decode_eof_owned(&mut src) {
+            assert_consumed(size, len, context);
+        }
+    }
+
+    // The smallest buffer that never fills up, reading the whole input at once.
+    let max_buffer_size = CONFORMANCE_BUFFER_SIZES
+        .iter()
+        .copied()
+        .find(|size| *size > input.len())
+        .expect("The input is shorter than 1024 bytes");
+
+    let reference =
+        decode_checked_sized(decoder.clone(), input, input.len().max(1), max_buffer_size);
+
+    assert_frames(&reference.frames, expected, || String::from("whole input"));
+
+    // A frame decoded on EOF and bytes remaining on EOF need one more byte in the buffer to read the EOF.
+    let min_buffer_size = reference
+        .sizes
+        .iter()
+        .map(|(size, eof)| size + *eof as usize)
+        .chain(match reference.error {
+            FramedReadError::BytesRemainingOnStream => Some(reference.remaining + 1),
+            FramedReadError::Decode(_) => Some(reference.remaining),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let buffer_sizes = CONFORMANCE_BUFFER_SIZES
+        .iter()
+        .copied()
+        .filter(|size| (min_buffer_size..=max_buffer_size).contains(size));
+
+    // A read never exceeds the free space of the buffer, so larger chunk sizes read the same.
+    for buffer_size in buffer_sizes {
+        for chunk_size in 1..=buffer_size.min(input.len().max(1)) {
+            let checked = decode_checked_sized(decoder.clone(), input, chunk_size, buffer_size);
+
+            assert_frames(&checked.frames, expected, || {
+                format!("chunk size {chunk_size}, buffer size {buffer_size}")
+            });
+
+            assert_eq!(
+                discriminant(&reference.error),
+                discriminant(&checked.error),
+                "Unexpected error with chunk size {chunk_size}, buffer size {buffer_size}: {:?}, expected: {:?}",
+                checked.error,
+                reference.error,
+            );
+        }
+    }
+
+    reference.error
+}
+
+/// Runs [`assert_decoder_conformance`] on an `input` ending with a truncated frame, e.g. `b"Hello\nHow"`.
+///
+/// # Panics
+///
+/// Panics if an invariant is violated, if the frames differ from `expected` or if decoding does not stop with [`FramedReadError::BytesRemainingOnStream`].
+pub fn assert_decoder_conformance_truncated<D, T>(decoder: D, input: &[u8], expected: &[T])
+where
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq<T> + Debug,
+    D::Error: Debug,
+    T: Debug,
+{
+    let error = assert_decoder_conformance(decoder, input, expected);
+
+    assert!(
+        matches!(error, FramedReadError::BytesRemainingOnStream),
+        "Unexpected error: {error:?}"
+    );
+}
+
+/// The items shared by the conformance tests of the codecs encoding sequences of bytes.
+pub const CONFORMANCE_ITEMS: &[&[u8]] = &[b"Hello", b"Hello, world!", b"Hei", b"sup", b"Hey"];
+
+/// Returns the [`CONFORMANCE_ITEMS`] followed by the codec-specific `extra` items, e.g. bytes the codec escapes or an empty item.
+///
+/// # Panics
+///
+/// Panics if an item is longer than `N`.
+pub fn conformance_items<const N: usize>(extra: &[&[u8]]) -> Vec<heapless::Vec<u8, N>> {
+    CONFORMANCE_ITEMS
+        .iter()
+        .chain(extra)
+        .map(|item| heapless::Vec::from_slice(item).expect("The item fits into N bytes"))
+        .collect()
+}
+
+/// Asserts that `encoder` and `decoder` hold the invariants every codec must hold while encoding and decoding `items`.
+///
+/// The invariants are:
+/// - The encoded size of an item never exceeds the length of the destination.
+/// - Encoding an item into a destination smaller than its encoded size fails instead of panicking or truncating the item.
+/// - The encoded items, followed by the closing frame, are decoded back by `decoder` and decoding stops with [`FramedReadError::EOF`].
+/// - `decoder` holds the invariants of [`assert_decoder_conformance`] while decoding the encoded items.
+///
+/// Returns the encoded bytes.
+///
+/// # Panics
+///
+/// Panics if encoding into a destination of `N` bytes fails or if an invariant is violated.
+pub fn assert_codec_conformance<const N: usize, E, D, I>(
+    mut encoder: E,
+    decoder: D,
+    items: &[I],
+) -> Vec<u8>
+where
+    E: Encoder<I> + EncoderEof<Error = <E as Encoder<I>>::Error> + Clone,
+    <E as Encoder<I>>::Error: Debug,
+    D: DecoderOwned + Clone,
+    D::Item: PartialEq<I> + Debug,
+    D::Error: Debug,
+    I: Clone + Debug,
+{
+    let mut buf = [0_u8; N];
+    let mut encoded = Vec::new();
+
+    let encode = |encoder: &mut E, item: Option<&I>, dst: &mut [u8]| match item {
+        Some(item) => encoder.encode(item.clone(), dst),
+        None => encoder.encode_eof(dst),
+    };
+
+    for item in items.iter().map(Some).chain([None]) {
+        let size = encode(&mut encoder.clone(), item, &mut buf).expect("Must encode");
+
+        assert!(
+            size <= N,
+            "Encoded {size} bytes into a destination of {N} bytes, item: {item:?}"
+        );
+
+        for len in 0..size {
+            assert!(
+                encode(&mut encoder.clone(), item, &mut buf[..len]).is_err(),
+                "Encoded an item of {size} bytes into a destination of {len} bytes, item: {item:?}"
+            );
+        }
+
+        let size = encode(&mut encoder, item, &mut buf).expect("Must encode");
+
+        encoded.extend_from_slice(&buf[..size]);
+    }
+
+    let error = assert_decoder_conformance(decoder, &encoded, items);
+
+    assert!(
+        matches!(error, FramedReadError::EOF),
+        "Unexpected error: {error:?}"
+    );
+
+    encoded
+}
+
+/// Runs [`assert_codec_conformance`] with `codec` as both encoder and decoder on the [`conformance_items`] followed by `extra`, using buffers of `1024` bytes.
+///
+/// Returns the encoded bytes.
+///
+/// # Panics
+///
+/// Panics if an item is longer than `N` or if an invariant is violated.
+pub fn assert_codec_conformance_items<const N: usize, C>(codec: C, extra: &[&[u8]]) -> Vec<u8>
+where
+    C: Encoder<heapless::Vec<u8, N>>
+        + EncoderEof<Error = <C as Encoder<heapless::Vec<u8, N>>>::Error>
+        + DecoderOwned
+        + Clone,
+    <C as Encoder<heapless::Vec<u8, N>>>::Error: Debug,
+    <C as DecoderOwned>::Item: PartialEq<heapless::Vec<u8, N>> + Debug,
+    <C as DecoderOwned>::Error: Debug,
+{
+    assert_codec_conformance::<1024, _, _, _>(codec.clone(), codec, &conformance_items::<N>(extra))
+}
+
+/// Repeats [`assert_decode_chunked`] for several buffer sizes.
+///
+/// Every buffer size must yield the `expected` byte strings and stop with the given kind of [`FramedReadError`].
+#[cfg(test)]
+macro_rules! assert_decode_buffer_sizes {
+    ($decoder:expr, $input:expr, $expected:expr, $error:ident, [$($buffer_size:literal),+ $(,)?]) => {
+        $(
+            let error = $crate::testing::assert_decode_chunked::<$buffer_size, _, _, _>($decoder, $input, &$crate::testing::to_vecs($expected)).await;
+
+            assert!(
+                matches!(error, $crate::FramedReadError::$error),
+                "Unexpected error with buffer size {}: {:?}",
+                $buffer_size,
+                error,
+            );
+        )+
+    };
+}
+
+#[cfg(test)]
+pub(crate) use assert_decode_buffer_sizes;
@@ -1,7 +1,18 @@
 //! Compatibility wrapper for [`Tokio's AsyncRead`](tokio::io::AsyncRead) and [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
 
-use crate::io::{AsyncRead as CrateAsyncRead, AsyncWrite as CrateAsyncWrite};
-use core::borrow::{Borrow, BorrowMut};
+#[cfg(feature = "tokio")]
+pub mod codec;
+
+#[cfg(feature = "tokio")]
+pub use self::codec::*;
+
+use crate::io::{
+    AsyncBufRead as CrateAsyncBufRead, AsyncRead as CrateAsyncRead, AsyncWrite as CrateAsyncWrite,
+};
+use core::{
+    borrow::{Borrow, BorrowMut},
+    pin::Pin,
+};
 
 /// Compatibility wrapper for [`Tokio's AsyncRead`](tokio::io::AsyncRead) and [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
 ///
@@ -68,7 +79,7 @@ impl<R> From<R> for Compat<R> {
 }
 
 const _: () = {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
 
     impl<R> CrateAsyncRead for Compat<R>
     where
@@ -84,6 +95,19 @@ const _: () = {
         }
     }
 
+    impl<R> CrateAsyncBufRead for Compat<R>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        fn fill_buf(&mut self) -> impl core::future::Future<Output = Result<&[u8], Self::Error>> {
+            AsyncBufReadExt::fill_buf(&mut self.0)
+        }
+
+        fn consume(&mut self, amt: usize) {
+            AsyncBufReadExt::consume(&mut self.0, amt)
+        }
+    }
+
     impl<W> CrateAsyncWrite for Compat<W>
     where
         W: tokio::io::AsyncWrite + Unpin,
@@ -100,5 +124,412 @@ const _: () = {
         fn flush(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
             self.0.flush()
         }
+
+        fn shutdown(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            self.0.shutdown()
+        }
+    }
+};
+
+pin_project_lite::pin_project! {
+    /// Pin-projected compatibility wrapper for `!Unpin` [`Tokio's AsyncRead`](tokio::io::AsyncRead) and [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
+    ///
+    /// - Converts a pinned `!Unpin` [`Tokio's AsyncRead`](tokio::io::AsyncRead) into a [`Crate's AsyncRead`](crate::io::AsyncRead).
+    /// - Converts a pinned `!Unpin` [`Tokio's AsyncWrite`](tokio::io::AsyncWrite) into a [`Crate's AsyncWrite`](crate::io::AsyncWrite).
+    ///
+    /// The crate's traits are implemented for `Pin<&mut PinCompat<R>>`, so the wrapper must be pinned first,
+    /// e.g. using [`core::pin::pin!`] or `Box::pin`. For `Unpin` transports use [`Compat`].
+    #[derive(Debug)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct PinCompat<R> {
+        #[pin]
+        inner: R,
+    }
+}
+
+impl<R> PinCompat<R> {
+    /// Creates a new [`PinCompat`] from a [`Tokio's AsyncRead`](tokio::io::AsyncRead) or [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
+    #[inline]
+    pub const fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the inner [`Tokio's AsyncRead`](tokio::io::AsyncRead) or [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
+    #[inline]
+    pub const fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a pinned mutable reference to the inner [`Tokio's AsyncRead`](tokio::io::AsyncRead) or [`Tokio's AsyncWrite`](tokio::io::AsyncWrite).
+    #[inline]
+    pub fn inner_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Returns the inner [`Tokio's AsyncRead`](tokio::io::AsyncRead) or [`Tokio's AsyncWrite`](tokio::io::AsyncWrite) consuming this [`PinCompat`].
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> From<R> for PinCompat<R> {
+    fn from(inner: R) -> Self {
+        Self::new(inner)
+    }
+}
+
+const _: () = {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    impl<R> CrateAsyncRead for Pin<&mut PinCompat<R>>
+    where
+        R: tokio::io::AsyncRead,
+    {
+        type Error = tokio::io::Error;
+
+        fn read<'a>(
+            &'a mut self,
+            buf: &'a mut [u8],
+        ) -> impl core::future::Future<Output = Result<usize, Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.read(buf).await }
+        }
+    }
+
+    impl<W> CrateAsyncWrite for Pin<&mut PinCompat<W>>
+    where
+        W: tokio::io::AsyncWrite,
+    {
+        type Error = tokio::io::Error;
+
+        fn write_all<'a>(
+            &'a mut self,
+            buf: &'a [u8],
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.write_all(buf).await }
+        }
+
+        fn flush(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.flush().await }
+        }
+
+        fn shutdown(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>> {
+            let mut inner = self.as_mut().inner_pin_mut();
+
+            async move { inner.shutdown().await }
+        }
     }
 };
+
+pub use crate::reverse_compat::{ReverseCompat, SendAsyncRead, SendAsyncWrite, SendReverseCompat};
+
+const _: () = {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    impl<R> SendAsyncRead for Compat<R>
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+    {
+        fn read_send<'a>(
+            &'a mut self,
+            buf: &'a mut [u8],
+        ) -> impl core::future::Future<Output = Result<usize, Self::Error>> + Send + 'a {
+            self.0.read(buf)
+        }
+    }
+
+    impl<W> SendAsyncWrite for Compat<W>
+    where
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        fn write_all_send<'a>(
+            &'a mut self,
+            buf: &'a [u8],
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + 'a {
+            self.0.write_all(buf)
+        }
+
+        fn flush_send(
+            &mut self,
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + '_ {
+            self.0.flush()
+        }
+
+        fn shutdown_send(
+            &mut self,
+        ) -> impl core::future::Future<Output = Result<(), Self::Error>> + Send + '_ {
+            self.0.shutdown()
+        }
+    }
+};
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use core::marker::PhantomPinned;
+    use std::vec::Vec;
+
+    use futures::StreamExt;
+
+    #[cfg(feature = "tokio")]
+    use std::string::String;
+    use tokio::io::AsyncWriteExt;
+    #[cfg(feature = "tokio")]
+    use tokio_util::codec::{FramedRead as TokioFramedRead, LinesCodec as TokioLinesCodec};
+
+    use crate::{test::init_tracing, FramedRead, FramedWrite, LinesCodec, LinesCodecOwned};
+
+    use super::*;
+
+    const LINES: &[u8] = b"Hello\nHello, world!\nHei\nsup\nHey\n";
+
+    pin_project_lite::pin_project! {
+        /// A `!Unpin` transport.
+        struct NotUnpin<T> {
+            #[pin]
+            inner: T,
+            #[pin]
+            _pinned: PhantomPinned,
+        }
+    }
+
+    impl<T> NotUnpin<T> {
+        fn new(inner: T) -> Self {
+            Self {
+                inner,
+                _pinned: PhantomPinned,
+            }
+        }
+    }
+
+    impl<T: tokio::io::AsyncRead> tokio::io::AsyncRead for NotUnpin<T> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> core::task::Poll<std::io::Result<()>> {
+            self.project().inner.poll_read(cx, buf)
+        }
+    }
+
+    impl<T: tokio::io::AsyncWrite> tokio::io::AsyncWrite for NotUnpin<T> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+            buf: &[u8],
+        ) -> core::task::Poll<std::io::Result<usize>> {
+            self.project().inner.poll_write(cx, buf)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<std::io::Result<()>> {
+            self.project().inner.poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            self: Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<std::io::Result<()>> {
+            self.project().inner.poll_shutdown(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn pin_compat() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(8);
+
+        let read = core::pin::pin!(PinCompat::new(NotUnpin::new(read)));
+        let write = core::pin::pin!(PinCompat::new(NotUnpin::new(write)));
+
+        let mut reader =
+            FramedRead::new_with_buffer(LinesCodecOwned::<32>::new(), read, [0_u8; 64]);
+        let mut writer = FramedWrite::new_with_buffer(LinesCodec::new(), write, [0_u8; 64]);
+
+        let write = async move {
+            for item in LINES.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+                writer.send_frame(item).await.expect("Must send");
+            }
+
+            writer.close().await.expect("Must close");
+        };
+
+        let read = async move {
+            reader
+                .stream()
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+        };
+
+        let (_, collected) = tokio::join!(write, read);
+
+        let expected: Vec<heapless::Vec<u8, 32>> = LINES
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| heapless::Vec::from_slice(line).unwrap())
+            .collect();
+
+        assert_eq!(expected, collected);
+    }
+
+    async fn write_lines(write: tokio::io::DuplexStream) {
+        let mut write = ReverseCompat::new(Compat::new(write));
+
+        for chunk in LINES.chunks(3) {
+            write.write_all(chunk).await.expect("Must write");
+        }
+
+        write.shutdown().await.expect("Must shutdown");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn reverse_compat_framed_read() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(8);
+
+        let read = async move {
+            TokioFramedRead::new(
+                ReverseCompat::new(Compat::new(read)),
+                TokioLinesCodec::new(),
+            )
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Must decode")
+        };
+
+        let (_, collected) = tokio::join!(write_lines(write), read);
+
+        let expected: Vec<String> = ["Hello", "Hello, world!", "Hei", "sup", "Hey"]
+            .map(String::from)
+            .into();
+
+        assert_eq!(expected, collected);
+    }
+
+    #[tokio::test]
+    async fn reverse_compat_copy() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(8);
+
+        let read = async move {
+            let mut collected = Vec::new();
+
+            tokio::io::copy(&mut ReverseCompat::new(Compat::new(read)), &mut collected)
+                .await
+                .expect("Must copy");
+
+            collected
+        };
+
+        let (_, collected) = tokio::join!(write_lines(write), read);
+
+        assert_eq!(LINES, collected);
+    }
+
+    #[tokio::test]
+    async fn send_reverse_compat_spawn() {
+        init_tracing();
+
+        let (read, write) = tokio::io::duplex(8);
+
+        let write = tokio::spawn(async move {
+            let mut write = SendReverseCompat::new(Compat::new(write));
+
+            for chunk in LINES.chunks(3) {
+                write.write_all(chunk).await.expect("Must write");
+            }
+
+            write.shutdown().await.expect("Must shutdown");
+        });
+
+        let read = tokio::spawn(async move {
+            let mut collected = Vec::new();
+
+            tokio::io::copy(
+                &mut SendReverseCompat::new(Compat::new(read)),
+                &mut collected,
+            )
+            .await
+            .expect("Must copy");
+
+            collected
+        });
+
+        write.await.expect("Must join");
+
+        assert_eq!(LINES, read.await.expect("Must join"));
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn reverse_compat_write_error() {
+        init_tracing();
+
+        let mut write = ReverseCompat::new(heapless::Vec::<u8, 4>::new());
+
+        assert_eq!(4, write.write(b"Hell").await.expect("Must write"));
+
+        let err = write.write(b"o").await.expect_err("Must fail");
+
+        assert_eq!(std::io::ErrorKind::WriteZero, err.kind());
+    }
+
+    #[tokio::test]
+    async fn reverse_compat_flush_keeps_pending_write_result() {
+        init_tracing();
+
+        use core::{pin::Pin, task::Poll};
+
+        use tokio::io::{AsyncReadExt, AsyncWrite};
+
+        let mut cx = core::task::Context::from_waker(futures::task::noop_waker_ref());
+
+        let (mut read, write) = tokio::io::duplex(2);
+        let mut write = ReverseCompat::new(Compat::new(write));
+
+        assert!(Pin::new(&mut write)
+            .poll_write(&mut cx, b"Hey")
+            .is_pending());
+
+        let mut buf = [0_u8; 8];
+
+        assert_eq!(2, read.read(&mut buf).await.expect("Must read"));
+
+        assert!(matches!(
+            Pin::new(&mut write).poll_flush(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+
+        // The write completed during the flush, polling it again returns its result instead of writing again.
+        assert!(matches!(
+            Pin::new(&mut write).poll_write(&mut cx, b"Hey"),
+            Poll::Ready(Ok(3))
+        ));
+
+        drop(write);
+
+        let mut rest = Vec::new();
+
+        read.read_to_end(&mut rest).await.expect("Must read");
+
+        assert_eq!(b"y", &rest[..]);
+    }
+}
@@ -0,0 +1,836 @@
+//! Bridges between the crate's codecs and [`Tokio-util's codecs`](tokio_util::codec).
+//!
+//! [`TokioCodec`] replaces the former `bincode::tokio_codec` implementation: use `TokioCodec<BincodeCodec<D>>`,
+//! its errors are [`TokioDecodeError<BincodeDecodeError>`](TokioDecodeError) and [`TokioEncodeError<BincodeEncodeError>`](TokioEncodeError).
+
+extern crate std;
+
+use std::vec::Vec;
+
+use tokio_util::bytes::{Buf, BytesMut};
+
+use crate::{
+    decode::{Decoder, DecoderOwned},
+    encode::{BufferTooSmallError, Encoder, EncoderEof},
+};
+
+/// The default initial number of bytes of the buffer used to encode a frame.
+pub const DEFAULT_ENCODE_CAPACITY: usize = 1024;
+
+/// The default maximum number of bytes of the buffer used to encode a frame.
+pub const DEFAULT_MAX_ENCODE_CAPACITY: usize = 8 * 1024 * 1024;
+
+/// Wraps a crate codec and implements [`Tokio-util's Decoder`](tokio_util::codec::Decoder) and [`Tokio-util's Encoder`](tokio_util::codec::Encoder) for it.
+///
+/// - [`Tokio-util's Decoder`](tokio_util::codec::Decoder) is implemented for every [`DecoderOwned`].
+///   A [`Decoder`] can not be bridged: its items may borrow from the buffer, but a `tokio-util` item must outlive the [`BytesMut`] it was decoded from.
+///   Codecs yielding borrowed items provide an owned variant, e.g. [`LinesCodecOwned`](crate::LinesCodecOwned).
+/// - [`Tokio-util's Encoder`](tokio_util::codec::Encoder) is implemented for every [`Encoder`] of a [`Clone`] item whose error implements [`BufferTooSmallError`].
+///
+/// An [`Encoder`] writes into a fixed size slice, so frames are encoded into an internal buffer of [`TokioCodec::encode_capacity`] bytes and copied into the destination.
+/// If the buffer is too small (see [`BufferTooSmallError`]), it is doubled and the encoding is retried with a clone of the item, up to [`TokioCodec::max_encode_capacity`] bytes.
+/// Every other error is returned right away.
+/// The buffer is kept between frames, so it is only zeroed when it grows. It shrinks back to [`TokioCodec::encode_capacity`] bytes when an item can not be encoded.
+#[derive(Debug, Clone)]
+pub struct TokioCodec<C> {
+    inner: C,
+    encode_capacity: usize,
+    max_encode_capacity: usize,
+    /// Internal buffer the inner encoder writes into.
+    buf: Vec<u8>,
+}
+
+impl<C> TokioCodec<C> {
+    /// Creates a new [`TokioCodec`] with the given `inner` codec.
+    #[inline]
+    pub const fn new(inner: C) -> Self {
+        Self::with_encode_capacity(inner, DEFAULT_ENCODE_CAPACITY)
+    }
+
+    /// Creates a new [`TokioCodec`] with the given `inner` codec and initial `encode_capacity`.
+    #[inline]
+    pub const fn with_encode_capacity(inner: C, encode_capacity: usize) -> Self {
+        Self {
+            inner,
+            encode_capacity,
+            max_encode_capacity: DEFAULT_MAX_ENCODE_CAPACITY,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Sets the maximum number of bytes of the buffer used to encode a frame.
+    #[inline]
+    pub const fn with_max_encode_capacity(mut self, max_encode_capacity: usize) -> Self {
+        self.max_encode_capacity = max_encode_capacity;
+        self
+    }
+
+    /// Returns the initial number of bytes of the buffer used to encode a frame.
+    #[inline]
+    pub const fn encode_capacity(&self) -> usize {
+        self.encode_capacity
+    }
+
+    /// Returns the maximum number of bytes of the buffer used to encode a frame.
+    #[inline]
+    pub const fn max_encode_capacity(&self) -> usize {
+        self.max_encode_capacity
+    }
+
+    /// Returns a reference to the inner codec.
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner codec.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns the inner codec consuming this [`TokioCodec`].
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<C: defmt::Format> defmt::Format for TokioCodec<C> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "TokioCodec {{ inner: {}, encode_capacity: {}, max_encode_capacity: {} }}",
+            self.inner,
+            self.encode_capacity,
+            self.max_encode_capacity
+        )
+    }
+}
+
+impl<C> From<C> for TokioCodec<C> {
+    fn from(inner: C) -> Self {
+        Self::new(inner)
+    }
+}
+
+/// An error that can occur when decoding a frame using a [`TokioCodec`].
+#[derive(Debug)]
+pub enum TokioDecodeError<E> {
+    /// An IO error occurred.
+    IO(tokio::io::Error),
+    /// The inner decoder returned an error.
+    Decode(E),
+}
+
+impl<E> From<tokio::io::Error> for TokioDecodeError<E> {
+    fn from(err: tokio::io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl<E> core::fmt::Display for TokioDecodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {}", err),
+            Self::Decode(err) => write!(f, "Decode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for TokioDecodeError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+/// An error that can occur when encoding a frame using a [`TokioCodec`].
+#[derive(Debug)]
+pub enum TokioEncodeError<E> {
+    /// An IO error occurred.
+    IO(tokio::io::Error),
+    /// The inner encoder returned an error.
+    Encode(E),
+}
+
+impl<E> From<tokio::io::Error> for TokioEncodeError<E> {
+    fn from(err: tokio::io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl<E> core::fmt::Display for TokioEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "IO error: {}", err),
+            Self::Encode(err) => write!(f, "Encode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for TokioEncodeError<E> where E: core::fmt::Display + core::fmt::Debug {}
+
+impl<C> tokio_util::codec::Decoder for TokioCodec<C>
+where
+    C: DecoderOwned,
+{
+    type Item = C::Item;
+    type Error = TokioDecodeError<C::Error>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.inner.decode_owned(src) {
+            Ok(Some((item, size))) => {
+                src.advance(size);
+
+                Ok(Some(item))
+            }
+            Ok(None) => Ok(None),
+            Err(err) => Err(TokioDecodeError::Decode(err)),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.inner.decode_eof_owned(src) {
+            Ok(Some((item, size))) => {
+                src.advance(size);
+
+                Ok(Some(item))
+            }
+            Ok(None) if src.is_empty() => Ok(None),
+            Ok(None) => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::UnexpectedEof,
+                "bytes remaining on stream",
+            )
+            .into()),
+            Err(err) => Err(TokioDecodeError::Decode(err)),
+        }
+    }
+}
+
+impl<C, I> tokio_util::codec::Encoder<I> for TokioCodec<C>
+where
+    C: Encoder<I>,
+    C::Error: BufferTooSmallError,
+    I: Clone,
+{
+    type Error = TokioEncodeError<C::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        if self.buf.len() < self.encode_capacity {
+            self.buf.resize(self.encode_capacity, 0);
+        }
+
+        loop {
+            let result = if self.buf.len() < self.max_encode_capacity {
+                self.inner.encode(item.clone(), &mut self.buf)
+            } else {
+                // The last attempt moves the item.
+                return self.encode_last(item, dst);
+            };
+
+            match result {
+                Ok(size) => {
+                    dst.extend_from_slice(&self.buf[..size]);
+
+                    return Ok(());
+                }
+                Err(err) if err.is_buffer_too_small() => {
+                    let len = core::cmp::min(
+                        core::cmp::max(self.buf.len(), 1) * 2,
+                        self.max_encode_capacity,
+                    );
+
+                    self.buf.resize(len, 0);
+                }
+                Err(err) => {
+                    self.shrink();
+
+                    return Err(TokioEncodeError::Encode(err));
+                }
+            }
+        }
+    }
+}
+
+impl<C> TokioCodec<C> {
+    /// Encodes `item` into the buffer of [`TokioCodec::max_encode_capacity`] bytes.
+    fn encode_last<I>(
+        &mut self,
+        item: I,
+        dst: &mut BytesMut,
+    ) -> Result<(), TokioEncodeError<C::Error>>
+    where
+        C: Encoder<I>,
+    {
+        match self.inner.encode(item, &mut self.buf) {
+            Ok(size) => {
+                dst.extend_from_slice(&self.buf[..size]);
+
+                Ok(())
+            }
+            Err(err) => {
+                self.shrink();
+
+                Err(TokioEncodeError::Encode(err))
+            }
+        }
+    }
+
+    /// Releases a buffer that grew beyond [`TokioCodec::encode_capacity`] for an item that could not be encoded.
+    fn shrink(&mut self) {
+        if self.buf.len() > self.encode_capacity {
+            self.buf.truncate(self.encode_capacity);
+            self.buf.shrink_to_fit();
+        }
+    }
+}
+
+/// Compatibility wrapper for [`Tokio-util's Decoder`](tokio_util::codec::Decoder) and [`Tokio-util's Encoder`](tokio_util::codec::Encoder).
+///
+/// - Converts a [`Tokio-util's Decoder`](tokio_util::codec::Decoder) into a [`Crate's Decoder`](crate::decode::Decoder) and a [`Crate's DecoderOwned`](crate::decode::DecoderOwned).
+/// - Converts a [`Tokio-util's Encoder`](tokio_util::codec::Encoder) into a [`Crate's Encoder`](crate::encode::Encoder).
+///
+/// The inner decoder works on an internal [`BytesMut`] that persists between calls: only the bytes appended to the slice since the last call are copied into it.
+/// Therefore, like the crate's own decoders, every slice must start at the first byte not consumed by a decoded frame,
+/// and must only grow between two calls that do not yield a frame. This is how [`FramedRead`](crate::FramedRead) calls its decoder.
+/// Call [`CodecCompat::clear`] before decoding an unrelated slice.
+///
+/// The number of bytes the inner decoder advanced the [`BytesMut`] is returned as the consumed size of the frame.
+/// Bytes the inner decoder discards without yielding a frame can only be reported with the next frame,
+/// so they stay in the framer's buffer until then and count towards its size.
+#[derive(Debug, Clone, Default)]
+pub struct CodecCompat<C> {
+    inner: C,
+    /// Internal buffer handed to the inner decoder.
+    buf: BytesMut,
+    /// Internal buffer handed to the inner encoder.
+    encode_buf: BytesMut,
+    /// Number of bytes of the slice that were copied into `buf` since the last frame.
+    seen: usize,
+}
+
+impl<C> CodecCompat<C> {
+    /// Creates a new [`CodecCompat`] from a [`Tokio-util's Decoder`](tokio_util::codec::Decoder) or [`Tokio-util's Encoder`](tokio_util::codec::Encoder).
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            buf: BytesMut::new(),
+            encode_buf: BytesMut::new(),
+            seen: 0,
+        }
+    }
+
+    /// Returns a reference to the inner [`Tokio-util's Decoder`](tokio_util::codec::Decoder) or [`Tokio-util's Encoder`](tokio_util::codec::Encoder).
+    #[inline]
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner [`Tokio-util's Decoder`](tokio_util::codec::Decoder) or [`Tokio-util's Encoder`](tokio_util::codec::Encoder).
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns the inner [`Tokio-util's Decoder`](tokio_util::codec::Decoder) or [`Tokio-util's Encoder`](tokio_util::codec::Encoder) consuming this [`CodecCompat`].
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Clears the bytes copied from the slice so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.seen = 0;
+    }
+
+    /// Copies the new bytes of `src` and calls the inner decoder.
+    fn decode_with<I, E>(
+        &mut self,
+        src: &[u8],
+        decode: impl FnOnce(&mut C, &mut BytesMut) -> Result<Option<I>, E>,
+    ) -> Result<Option<(I, usize)>, E> {
+        if let Some(new) = src.get(self.seen..) {
+            self.buf.extend_from_slice(new);
+            self.seen = src.len();
+        }
+
+        match decode(&mut self.inner, &mut self.buf) {
+            Ok(Some(item)) => {
+                let size = self.seen.saturating_sub(self.buf.len());
+
+                // The remaining bytes start the next slice.
+                self.seen = self.buf.len();
+
+                Ok(Some((item, size)))
+            }
+            Ok(None) => Ok(None),
+            Err(err) => {
+                self.clear();
+
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<C> From<C> for CodecCompat<C> {
+    fn from(inner: C) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<C> DecoderOwned for CodecCompat<C>
+where
+    C: tokio_util::codec::Decoder,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode_owned(&mut self, src: &mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_with(src, |inner, buf| inner.decode(buf))
+    }
+
+    fn decode_eof_owned(
+        &mut self,
+        src: &mut [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_with(src, |inner, buf| inner.decode_eof(buf))
+    }
+}
+
+impl<'buf, C> Decoder<'buf> for CodecCompat<C>
+where
+    C: tokio_util::codec::Decoder,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &'buf mut [u8]) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_owned(src)
+    }
+
+    fn decode_eof(
+        &mut self,
+        src: &'buf mut [u8],
+    ) -> Result<Option<(Self::Item, usize)>, Self::Error> {
+        self.decode_eof_owned(src)
+    }
+}
+
+/// An error that can occur when encoding a frame using a [`CodecCompat`].
+#[derive(Debug)]
+pub enum CodecCompatEncodeError<E> {
+    /// The input buffer is too small to fit the encoded frame.
+    BufferTooSmall,
+    /// The inner encoder returned an error.
+    Encode(E),
+}
+
+impl<E> core::fmt::Display for CodecCompatEncodeError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::Encode(err) => write!(f, "Encode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for CodecCompatEncodeError<E> where
+    E: core::fmt::Display + core::fmt::Debug
+{
+}
+
+impl<E> BufferTooSmallError for CodecCompatEncodeError<E> {
+    fn is_buffer_too_small(&self) -> bool {
+        matches!(self, Self::BufferTooSmall)
+    }
+}
+
+impl<C, I> Encoder<I> for CodecCompat<C>
+where
+    C: tokio_util::codec::Encoder<I>,
+{
+    type Error = CodecCompatEncodeError<C::Error>;
+
+    fn encode(&mut self, item: I, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.encode_buf.clear();
+
+        self.inner
+            .encode(item, &mut self.encode_buf)
+            .map_err(CodecCompatEncodeError::Encode)?;
+
+        let size = self.encode_buf.len();
+
+        if dst.len() < size {
+            return Err(CodecCompatEncodeError::BufferTooSmall);
+        }
+
+        dst[..size].copy_from_slice(&self.encode_buf);
+
+        Ok(size)
+    }
+}
+
+/// [`Tokio-util's Encoder`](tokio_util::codec::Encoder) has no closing frame.
+impl<C> EncoderEof for CodecCompat<C> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead as TokioFramedRead, FramedWrite as TokioFramedWrite};
+
+    use crate::{
+        test::init_tracing, tokio::Compat, AnyDelimiterCodecOwned, FramedRead, FramedReadError,
+        FramedWrite, LengthCodecOwned, LinesCodecOwned,
+    };
+
+    use super::*;
+
+    fn items() -> Vec<heapless::Vec<u8, 32>> {
+        std::vec![
+            heapless::Vec::from_slice(b"Hello").unwrap(),
+            heapless::Vec::from_slice(b"Hello, world!").unwrap(),
+            heapless::Vec::from_slice(b"Hei").unwrap(),
+            heapless::Vec::from_slice(b"sup").unwrap(),
+            heapless::Vec::from_slice(b"Hey").unwrap(),
+        ]
+    }
+
+    /// Sends from a `FramedWrite` to a `tokio_util::codec::FramedRead` and from a `tokio_util::codec::FramedWrite` to a `FramedRead`.
+    macro_rules! roundtrip {
+        ($codec:expr) => {{
+            let (read, write) = tokio::io::duplex(8);
+
+            tokio::spawn(async move {
+                let mut writer =
+                    FramedWrite::new_with_buffer($codec, Compat::new(write), [0_u8; 64]);
+
+                for item in items() {
+                    writer.send_frame(item).await.expect("Must send");
+                }
+            });
+
+            let collected = TokioFramedRead::new(read, TokioCodec::new($codec))
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .expect("Must decode");
+
+            assert_eq!(items(), collected);
+        }
+        {
+            let (read, write) = tokio::io::duplex(8);
+
+            tokio::spawn(async move {
+                let mut sink = TokioFramedWrite::new(write, TokioCodec::new($codec));
+
+                for item in items() {
+                    sink.send(item).await.expect("Must send");
+                }
+            });
+
+            let mut framer = FramedRead::new_with_buffer($codec, Compat::new(read), [0_u8; 64]);
+
+            let collected = framer
+                .stream()
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            assert_eq!(items(), collected);
+        }};
+    }
+
+    #[tokio::test]
+    async fn lines() {
+        init_tracing();
+
+        roundtrip!(LinesCodecOwned::<32>::new());
+    }
+
+    #[tokio::test]
+    async fn length() {
+        init_tracing();
+
+        roundtrip!(LengthCodecOwned::<32>::new());
+    }
+
+    #[tokio::test]
+    async fn any_delimiter() {
+        init_tracing();
+
+        roundtrip!(AnyDelimiterCodecOwned::<32>::new(b"###"));
+    }
+
+    #[test]
+    fn encode_grows_buffer() {
+        init_tracing();
+
+        let item = heapless::Vec::<u8, 4096>::from_slice(&[b'a'; 3000]).unwrap();
+
+        let mut codec = TokioCodec::with_encode_capacity(LengthCodecOwned::<4096>::new(), 16);
+        let mut dst = BytesMut::new();
+
+        tokio_util::codec::Encoder::encode(&mut codec, item.clone(), &mut dst)
+            .expect("Must encode");
+
+        assert_eq!(3004, dst.len());
+        assert_eq!(&dst[4..], item.as_slice());
+
+        let item = tokio_util::codec::Decoder::decode(&mut codec, &mut dst)
+            .expect("Must decode")
+            .expect("Must yield");
+
+        assert_eq!(3000, item.len());
+
+        let mut codec = TokioCodec::with_encode_capacity(LengthCodecOwned::<4096>::new(), 16)
+            .with_max_encode_capacity(1024);
+
+        assert!(matches!(
+            tokio_util::codec::Encoder::encode(&mut codec, item, &mut dst),
+            Err(TokioEncodeError::Encode(_))
+        ));
+        assert!(dst.is_empty());
+
+        // The buffer does not stay grown after a failure.
+        assert_eq!(16, codec.buf.len());
+    }
+
+    #[test]
+    fn encode_returns_other_errors_right_away() {
+        init_tracing();
+
+        /// An encoder failing with an error that is not about the buffer size.
+        struct Failing {
+            attempts: usize,
+        }
+
+        impl Encoder<u8> for Failing {
+            type Error = crate::LengthEncodeError;
+
+            fn encode(&mut self, _item: u8, _dst: &mut [u8]) -> Result<usize, Self::Error> {
+                self.attempts += 1;
+
+                Err(crate::LengthEncodeError::PayloadTooLarge)
+            }
+        }
+
+        let mut codec = TokioCodec::with_encode_capacity(Failing { attempts: 0 }, 16);
+        let mut dst = BytesMut::new();
+
+        assert!(matches!(
+            tokio_util::codec::Encoder::encode(&mut codec, 0, &mut dst),
+            Err(TokioEncodeError::Encode(
+                crate::LengthEncodeError::PayloadTooLarge
+            ))
+        ));
+
+        assert_eq!(1, codec.inner().attempts);
+        assert_eq!(16, codec.buf.len());
+    }
+
+    #[tokio::test]
+    async fn bytes_remaining_on_stream() {
+        init_tracing();
+
+        let (read, mut write) = tokio::io::duplex(8);
+
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+
+            write.write_all(b"Hello\nwor").await.expect("Must write");
+        });
+
+        let mut framer = TokioFramedRead::new(read, TokioCodec::new(LinesCodecOwned::<32>::new()));
+
+        let item = framer
+            .next()
+            .await
+            .expect("Must yield")
+            .expect("Must decode");
+        assert_eq!(b"Hello", item.as_slice());
+
+        let err = framer
+            .next()
+            .await
+            .expect("Must yield")
+            .expect_err("Must fail");
+        assert!(
+            matches!(err, TokioDecodeError::IO(err) if err.kind() == tokio::io::ErrorKind::UnexpectedEof)
+        );
+    }
+
+    /// A `tokio-util` decoder that skips bytes until `<` and yields the bytes until `>`.
+    struct AngleBracketsDecoder;
+
+    impl tokio_util::codec::Decoder for AngleBracketsDecoder {
+        type Item = Vec<u8>;
+        type Error = tokio::io::Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            match src.iter().position(|b| *b == b'<') {
+                None => {
+                    src.clear();
+
+                    Ok(None)
+                }
+                Some(start) => {
+                    src.advance(start);
+
+                    match src.iter().position(|b| *b == b'>') {
+                        None => Ok(None),
+                        Some(end) => {
+                            let frame = src.split_to(end + 1);
+
+                            Ok(Some(frame[1..end].to_vec()))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn codec_compat_framed_read() {
+        init_tracing();
+
+        let items: &[&[u8]] = &[b"Hel", b"lo\nHello, wor", b"ld!\nH", b"ei\n"];
+
+        let (read, mut write) = tokio::io::duplex(4);
+
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+
+            for item in items {
+                write.write_all(item).await.expect("Must write");
+            }
+        });
+
+        let mut framer = FramedRead::new_with_buffer(
+            CodecCompat::new(tokio_util::codec::LinesCodec::new()),
+            Compat::new(read),
+            [0_u8; 32],
+        );
+
+        let collected = framer
+            .stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        assert_eq!(["Hello", "Hello, world!", "Hei"].to_vec(), collected);
+    }
+
+    #[tokio::test]
+    async fn codec_compat_sink_stream() {
+        init_tracing();
+
+        let items = ["Hello", "Hello, world!", "Hei"];
+
+        let (read, write) = tokio::io::duplex(8);
+
+        tokio::spawn(async move {
+            let mut writer = FramedWrite::new_with_buffer(
+                CodecCompat::new(tokio_util::codec::LengthDelimitedCodec::new()),
+                Compat::new(write),
+                [0_u8; 32],
+            );
+
+            for item in items {
+                writer
+                    .send_frame(tokio_util::bytes::Bytes::from_static(item.as_bytes()))
+                    .await
+                    .expect("Must send");
+            }
+        });
+
+        let collected = TokioFramedRead::new(read, tokio_util::codec::LengthDelimitedCodec::new())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Must decode");
+
+        assert_eq!(items.map(str::as_bytes).to_vec(), collected);
+    }
+
+    #[test]
+    fn codec_compat_skipped_bytes() {
+        let mut codec = CodecCompat::new(AngleBracketsDecoder);
+
+        let src = &mut *b"garbage<Hel".to_vec();
+        assert!(codec.decode_owned(src).expect("Must decode").is_none());
+
+        // The skipped bytes are still part of the buffer and reported as consumed with the next frame.
+        let src = &mut *b"garbage<Hello>.".to_vec();
+        let (item, size) = codec
+            .decode_owned(src)
+            .expect("Must decode")
+            .expect("Must yield");
+
+        assert_eq!(b"Hello", item.as_slice());
+        assert_eq!(14, size);
+
+        // The remaining bytes were kept and only the new ones are appended.
+        let src = &mut *b".<Hi>".to_vec();
+        let (item, size) = codec
+            .decode_owned(src)
+            .expect("Must decode")
+            .expect("Must yield");
+
+        assert_eq!(b"Hi", item.as_slice());
+        assert_eq!(5, size);
+    }
+
+    #[tokio::test]
+    async fn codec_compat_skipped_bytes_fill_buffer() {
+        init_tracing();
+
+        let read: &[u8] = b"<Hi>garbage, garbage<Hey>";
+
+        let mut framer =
+            FramedRead::new_with_buffer(CodecCompat::new(AngleBracketsDecoder), read, [0_u8; 8]);
+
+        let collected = framer.stream().collect::<Vec<_>>().await;
+
+        // The skipped garbage is only released with the next frame, so it has to fit the framer's buffer.
+        assert_eq!(2, collected.len());
+        assert!(matches!(collected[0], Ok(ref item) if item == b"Hi"));
+        assert!(matches!(collected[1], Err(FramedReadError::BufferTooSmall)));
+    }
+
+    #[test]
+    fn codec_compat_buffer_too_small() {
+        let mut codec = CodecCompat::new(tokio_util::codec::LinesCodec::new());
+
+        let mut dst = [0_u8; 4];
+
+        assert!(matches!(
+            codec.encode("Hello", &mut dst),
+            Err(CodecCompatEncodeError::BufferTooSmall)
+        ));
+    }
+}